@@ -1,9 +1,17 @@
 use anchor_lang::prelude::*;
 use std::collections::HashMap;
-use crate::calculate_ticket_price_for_sol;
-use crate::TICKET_BTC_SATOSHIS;
+use crate::get_live_ticket_price_for_sol;
+use crate::TicketPrice;
+use crate::QUOTE_VALIDITY_SLOTS;
+use crate::AssetOracle;
+use crate::TicketAsset;
+use crate::TicketDenomination;
+use crate::PriceGuard;
+use crate::BeneficiaryShare;
+use crate::CommissionBalance;
 use crate::Round;
 use crate::RoundStatus;
+use crate::RaffleError;
 use crate::RaffleState;
 use crate::TokenRaffle;
     
@@ -12,11 +20,22 @@ pub fn get_raffle_state(ctx: Context<GetRaffleState>) -> Result<RaffleStateView>
     
     Ok(RaffleStateView {
         authority: state.authority,
-        entrance_fee_percentage: state.entrance_fee_percentage,
-        beneficiary: state.beneficiary,
+        entrance_fee_bps: state.entrance_fee_bps,
+        beneficiaries: state.beneficiaries.clone(),
         created_at: state.created_at,
         vrf_request_counter: state.vrf_request_counter,
         test_ticket_price: state.test_ticket_price,
+        btc_oracle: state.btc_oracle,
+        sol_oracle: state.sol_oracle,
+        price_lock_enabled: state.price_lock_enabled,
+        paused: state.paused,
+        pending_authority: state.pending_authority,
+        timelock_delay: state.timelock_delay,
+        rent_reserve_rounds: state.rent_reserve_rounds,
+        vrf_reserve_requests: state.vrf_reserve_requests,
+        vault_top_up_bps: state.vault_top_up_bps,
+        rent_vault_target: state.rent_vault_target,
+        vrf_vault_target: state.vrf_vault_target,
     })
 }
 
@@ -32,6 +51,14 @@ pub fn get_token_raffle(ctx: Context<GetTokenRaffle>) -> Result<TokenRaffleView>
         current_round_end_time: raffle.current_round_end_time,
         total_rounds: raffle.total_rounds,
         pending_rounds: raffle.pending_rounds.clone(),
+        ticket_denomination: raffle.ticket_denomination,
+        price_guard: raffle.price_guard,
+        last_ticket_price: raffle.last_ticket_price,
+        circuit_breaker_tripped: raffle.circuit_breaker_tripped,
+        accrued_commission: raffle.accrued_commission,
+        commission_balances: raffle.commission_balances.clone(),
+        unassigned_commission: raffle.unassigned_commission,
+        outstanding_obligations: raffle.outstanding_obligations,
     })
 }
 
@@ -99,12 +126,16 @@ pub fn get_raffle_round_data(ctx: Context<GetRoundAccounts>, round_id: u32) -> R
         end_time: round.end_time,
         prize_amount: round.prize_amount,
         commission_balance: round.commission_balance,
+        total_received: round.total_received,
         //cumulative_tickets: round.cumulative_tickets.clone(),
         winner_address: round.winner_address,
         winner_purchase_index: round.winner_purchase_index,
         winner_ticket_index: round.winner_ticket_index,
         round_players,
         prize_claimed: round.prize_claimed,
+        entrance_fee_bps: round.entrance_fee_bps,
+        beneficiaries: round.beneficiaries.clone(),
+        locked_ticket_price: round.locked_ticket_price,
     })
 }
 
@@ -132,17 +163,102 @@ pub fn get_raffle_round_count(ctx: Context<GetTockenRaffle>) -> Result<u32> {
     Ok(ctx.accounts.sol_raffle.total_rounds)
 }
     
-pub fn get_ticket_price_in_btc(ctx: Context<EmptyAccounts>) -> Result<u64> {
-    Ok(TICKET_BTC_SATOSHIS)
+/// Ticket price in satoshis for BTC-denominated SOL raffles.
+pub fn get_ticket_price_in_btc(ctx: Context<GetTockenRaffle>) -> Result<u64> {
+    let denomination = ctx.accounts.sol_raffle.ticket_denomination;
+    require!(
+        denomination.asset == TicketAsset::Btc,
+        RaffleError::InvalidTicketDenomination
+    );
+
+    Ok(denomination.amount)
 }
 
-pub fn get_ticket_price(ctx: Context<GetTicketPrice>) -> Result<u64> {
-    let ticket_price = calculate_ticket_price_for_sol(
-        &ctx.accounts.btc_price_feed,
-        &ctx.accounts.sol_price_feed,
+/// Returns the locked price of the current round while it is open, otherwise the live oracle price,
+/// together with the denomination it was derived from.
+pub fn get_ticket_price(ctx: Context<GetTicketPrice>) -> Result<TicketPriceView> {
+    let (ticket_price, denomination, locked) = current_ticket_price(&ctx.accounts, ctx.program_id)?;
+
+    Ok(TicketPriceView {
+        price: ticket_price.amount,
+        denomination,
+        locked,
+    })
+}
+
+/// Prices `count` tickets for `buy_tickets_sol_with_quote`. The quote is valid
+/// until `expiry_slot`; the purchase recomputes the cost and compares it with `total_cost`,
+/// and enforces the expiry from the oracle slots.
+pub fn get_ticket_quote(ctx: Context<GetTicketPrice>, count: u32) -> Result<TicketQuote> {
+    require!(count > 0, RaffleError::InvalidTicketCount);
+
+    let (ticket_price, denomination, locked) = current_ticket_price(&ctx.accounts, ctx.program_id)?;
+
+    let total_cost = ticket_price
+        .amount
+        .checked_mul(count as u64)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    let quoted_slot = Clock::get()?.slot;
+    // Counted from the oldest oracle update, as the purchase does
+    let priced_slot = [ticket_price.btc_oracle_slot, ticket_price.pay_oracle_slot]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or(quoted_slot);
+
+    Ok(TicketQuote {
+        count,
+        ticket_price: ticket_price.amount,
+        total_cost,
+        denomination,
+        locked,
+        btc_oracle_slot: ticket_price.btc_oracle_slot,
+        sol_oracle_slot: ticket_price.pay_oracle_slot,
+        quoted_slot,
+        expiry_slot: priced_slot + QUOTE_VALIDITY_SLOTS,
+    })
+}
+
+/// Price the next SOL purchase will pay: the open round's locked price if any,
+/// otherwise the live price in the denomination of the round being bought into.
+fn current_ticket_price(
+    accounts: &GetTicketPrice,
+    program_id: &Pubkey,
+) -> Result<(TicketPrice, TicketDenomination, bool)> {
+    let round_info = accounts.round.to_account_info();
+    // The next round picks up the raffle's current denomination
+    let mut denomination = accounts.sol_raffle.ticket_denomination;
+
+    if round_info.owner == program_id && round_info.data_len() > 0 {
+        let round_data = round_info.try_borrow_data()?;
+        let round = Round::try_deserialize(&mut &round_data[..])?;
+        let now = Clock::get()?.unix_timestamp;
+
+        if round.status == RoundStatus::Open && now < round.end_time {
+            if let Some(locked_price) = round.locked_ticket_price {
+                msg!("Round {} ticket price is locked: {} lamports", round.round_id, locked_price);
+                let ticket_price = TicketPrice {
+                    amount: locked_price,
+                    btc_oracle_slot: None,
+                    pay_oracle_slot: None,
+                };
+                return Ok((ticket_price, round.ticket_denomination, true));
+            }
+            denomination = round.ticket_denomination;
+        }
+    }
+
+    let ticket_price = get_live_ticket_price_for_sol(
+        &accounts.raffle_state,
+        &denomination,
+        &accounts.btc_price_feed,
+        &accounts.btc_secondary_price_feed,
+        &accounts.sol_price_feed,
+        &accounts.sol_secondary_price_feed,
     )?;
 
-    Ok(ticket_price)
+    Ok((ticket_price, denomination, false))
 }
 
 #[derive(Accounts)]
@@ -165,11 +281,44 @@ pub struct GetTokenRaffle<'info> {
 
 #[derive(Accounts)]
 pub struct GetTicketPrice<'info> {
-    /// CHECK: Switchboard BTC price feed
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        seeds = [b"sol_raffle"],
+        bump = sol_raffle.bump
+    )]
+    pub sol_raffle: Account<'info, TokenRaffle>,
+
+    /// CHECK: Current round PDA, may not exist yet; only read for its locked price
+    #[account(
+        seeds = [
+            b"round",
+            sol_raffle.key().as_ref(),
+            &sol_raffle.current_round_id.unwrap_or(0).to_le_bytes()
+        ],
+        bump
+    )]
+    pub round: UncheckedAccount<'info>,
+
+    /// CHECK: BTC primary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.btc_oracle.primary.address @ RaffleError::InvalidFeedAccount)]
     pub btc_price_feed: AccountInfo<'info>,
 
-    /// CHECK: Switchboard SOL price feed
+    /// CHECK: BTC secondary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.btc_oracle.secondary.address @ RaffleError::InvalidFeedAccount)]
+    pub btc_secondary_price_feed: AccountInfo<'info>,
+
+    /// CHECK: SOL primary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.sol_oracle.primary.address @ RaffleError::InvalidFeedAccount)]
     pub sol_price_feed: AccountInfo<'info>,
+
+    /// CHECK: SOL secondary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.sol_oracle.secondary.address @ RaffleError::InvalidFeedAccount)]
+    pub sol_secondary_price_feed: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
@@ -208,11 +357,22 @@ pub struct EmptyAccounts {}
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct RaffleStateView {
     pub authority: Pubkey,
-    pub entrance_fee_percentage: u8,
-    pub beneficiary: Pubkey,
+    pub entrance_fee_bps: u16,
+    pub beneficiaries: Vec<BeneficiaryShare>,
     pub created_at: i64,
     pub vrf_request_counter: u8,
     pub test_ticket_price: Option<u64>,
+    pub btc_oracle: AssetOracle,
+    pub sol_oracle: AssetOracle,
+    pub price_lock_enabled: bool,
+    pub paused: u8, // bitmask of PAUSE_* scopes
+    pub pending_authority: Option<Pubkey>,
+    pub timelock_delay: i64,
+    pub rent_reserve_rounds: u32,
+    pub vrf_reserve_requests: u32,
+    pub vault_top_up_bps: u16,
+    pub rent_vault_target: u64,
+    pub vrf_vault_target: u64,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
@@ -224,6 +384,34 @@ pub struct TokenRaffleView {
     pub current_round_end_time: Option<i64>,
     pub total_rounds: u32,
     pub pending_rounds: Vec<u32>,
+    pub ticket_denomination: TicketDenomination,
+    pub price_guard: PriceGuard,
+    pub last_ticket_price: Option<u64>,
+    pub circuit_breaker_tripped: bool,
+    pub accrued_commission: u64,
+    pub commission_balances: Vec<CommissionBalance>,
+    pub unassigned_commission: u64,
+    pub outstanding_obligations: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct TicketPriceView {
+    pub price: u64, // lamports
+    pub denomination: TicketDenomination,
+    pub locked: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct TicketQuote {
+    pub count: u32,
+    pub ticket_price: u64, // lamports
+    pub total_cost: u64,   // lamports
+    pub denomination: TicketDenomination,
+    pub locked: bool,
+    pub btc_oracle_slot: Option<u64>, // None when the BTC feed was not needed
+    pub sol_oracle_slot: Option<u64>, // None for locked, native or test prices
+    pub quoted_slot: u64,
+    pub expiry_slot: u64, // informational, purchases derive it from the oracle slots
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -250,11 +438,15 @@ pub struct RoundDataView {
     pub end_time: i64,
     pub prize_amount: u64,
     pub commission_balance: u64,
+    pub total_received: u64,
     pub winner_address: Option<Pubkey>,
     pub winner_purchase_index: Option<u32>,
     pub winner_ticket_index: Option<u32>,
     pub round_players: Vec<RoundPlayerDataWithAddress>,
     pub prize_claimed: bool,
+    pub entrance_fee_bps: u16,
+    pub beneficiaries: Vec<BeneficiaryShare>,
+    pub locked_ticket_price: Option<u64>,
 }
 
 // Error codes
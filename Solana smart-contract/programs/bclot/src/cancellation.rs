@@ -0,0 +1,219 @@
+use anchor_lang::prelude::*;
+use crate::{
+    change_round_status, RaffleError, RaffleState, Round, RoundStatus, RoundTickets,
+    RoundTicketsPurchase, TokenRaffle, PAUSE_CLAIMS, ROUND_LIVENESS_TIMEOUT,
+};
+
+/// Works on the SOL raffle and on any token raffle, once the round has ended: by the
+/// authority right away, by anyone after `ROUND_LIVENESS_TIMEOUT`. The round leaves
+/// the VRF queue, and a callback that still arrives for it is ignored.
+pub fn cancel_round(ctx: Context<CancelRound>, round_id: u32) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let caller = ctx.accounts.caller.key();
+    let token_raffle = &mut ctx.accounts.token_raffle;
+    let round = &mut ctx.accounts.round;
+
+    require!(round.status == RoundStatus::Open, RaffleError::RoundNotOpen);
+    require!(now >= round.end_time, RaffleError::RoundNotEndedYet);
+
+    let liveness_deadline = round
+        .end_time
+        .checked_add(ROUND_LIVENESS_TIMEOUT)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    require!(
+        caller == ctx.accounts.raffle_state.authority || now >= liveness_deadline,
+        RaffleError::Unauthorized
+    );
+
+    change_round_status(token_raffle, round, RoundStatus::Cancelled)?;
+    let vrf_pending = token_raffle.pending_rounds.contains(&round_id);
+    token_raffle.pending_rounds.retain(|&id| id != round_id);
+
+    if vrf_pending {
+        msg!("⚠️ Round {} was waiting for VRF, its callback will be ignored", round_id);
+    }
+
+    msg!(
+        "🛑 Round {} cancelled, {} refundable across {} purchases",
+        round_id,
+        round.total_received,
+        round.purchases_count
+    );
+
+    emit!(RoundCancelled {
+        token: token_raffle.token_mint,
+        round_id,
+        cancelled_by: caller,
+        refundable_amount: round.total_received,
+        vrf_pending,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Permissionless: the paid amount always goes back to the purchase's player,
+/// who also gets the purchase account rent when it is closed.
+pub fn refund_purchase(ctx: Context<RefundPurchase>, round_id: u32, purchase_index: u32) -> Result<()> {
+    ctx.accounts.raffle_state.require_not_paused(PAUSE_CLAIMS)?;
+
+    require!(
+        ctx.accounts.round.status == RoundStatus::Cancelled,
+        RaffleError::RoundNotCancelled
+    );
+
+    let amount = ctx.accounts.round_tickets_purchase.paid_amount;
+    // The vault has to stay rent exempt after the refund
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(ctx.accounts.vault.data_len());
+    let remaining = ctx
+        .accounts
+        .vault
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(RaffleError::InsufficientVaultBalance)?;
+    require!(
+        remaining >= rent_exempt_minimum,
+        RaffleError::InsufficientVaultBalance
+    );
+
+    **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount;
+    **ctx.accounts.player.try_borrow_mut_lamports()? += amount;
+    ctx.accounts.sol_raffle.settle_obligations(amount)?;
+
+    msg!("✓ Refunded {} lamports to {}", amount, ctx.accounts.player.key());
+
+    emit!(PurchaseRefunded {
+        token: ctx.accounts.sol_raffle.token_mint,
+        round_id,
+        purchase_index,
+        player: ctx.accounts.player.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Account validation structs
+#[derive(Accounts)]
+#[instruction(round_id: u32)]
+pub struct CancelRound<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    /// SOL raffle or any per-mint token raffle
+    #[account(
+        mut,
+        constraint = token_raffle.key() == token_raffle.address()? @ RaffleError::InvalidTokenRaffle
+    )]
+    pub token_raffle: Account<'info, TokenRaffle>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"round",
+            token_raffle.key().as_ref(),
+            &round_id.to_le_bytes()
+        ],
+        bump = round.bump
+    )]
+    pub round: Account<'info, Round>,
+
+    /// No winner will be drawn, so the tickets go back to the rent vault
+    #[account(
+        mut,
+        close = rent_vault,
+        seeds = [
+            b"round_tickets",
+            round.key().as_ref()
+        ],
+        bump
+    )]
+    pub round_tickets: AccountLoader<'info, RoundTickets>,
+
+    #[account(
+        mut,
+        seeds = [b"rent_vault"],
+        bump
+    )]
+    pub rent_vault: SystemAccount<'info>,
+
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: u32, purchase_index: u32)]
+pub struct RefundPurchase<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_raffle"],
+        bump = sol_raffle.bump
+    )]
+    pub sol_raffle: Account<'info, TokenRaffle>,
+
+    #[account(
+        seeds = [
+            b"round",
+            sol_raffle.key().as_ref(),
+            &round_id.to_le_bytes()
+        ],
+        bump = round.bump
+    )]
+    pub round: Account<'info, Round>,
+
+    /// Closed on refund, so it can't be refunded twice
+    #[account(
+        mut,
+        close = player,
+        seeds = [
+            b"round_tickets_purchase",
+            round.key().as_ref(),
+            &purchase_index.to_le_bytes()
+        ],
+        bump = round_tickets_purchase.bump,
+        has_one = player @ RaffleError::Unauthorized
+    )]
+    pub round_tickets_purchase: Account<'info, RoundTicketsPurchase>,
+
+    /// CHECK: receives the refund and the purchase rent, matched by `has_one`
+    #[account(mut)]
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    /// CHECK: vault for storing SOL
+    pub vault: AccountInfo<'info>,
+}
+
+// Events
+#[event]
+pub struct RoundCancelled {
+    pub token: Pubkey,
+    pub round_id: u32,
+    pub cancelled_by: Pubkey, // the program itself for rounds drawn without tickets
+    pub refundable_amount: u64,
+    pub vrf_pending: bool, // the round was queued for randomness when it was cancelled
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PurchaseRefunded {
+    pub token: Pubkey,
+    pub round_id: u32,
+    pub purchase_index: u32,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
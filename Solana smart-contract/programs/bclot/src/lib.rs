@@ -8,8 +8,6 @@ use anchor_lang::Discriminator;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
-use std::str::FromStr;
-use switchboard_on_demand::on_demand::accounts::pull_feed::PullFeedAccountData;
 use orao_solana_vrf_cb::{
     cpi,
     program::OraoVrfCb,
@@ -26,21 +24,73 @@ use std::cell::RefMut;
 declare_id!("Ah737jVNXFRoUMo8qyCGhBW4HyFz6MvKMVvEkgqm5o85");
 
 pub mod admin;
+pub mod cancellation;
+pub mod multisig;
+pub mod price_feeds;
+pub mod registry;
+pub mod solvency;
+pub mod spl;
+pub mod timelock;
 pub mod vrf;
 
 use admin::*;
+use cancellation::*;
+use multisig::*;
+use price_feeds::*;
+use registry::*;
+use solvency::*;
+use spl::*;
+use timelock::*;
 use vrf::*;
 
 // Constants
-const LAMPORTS_PER_SOL: u64 = 1_000_000_000u64;
-const TICKET_BTC_STR: &str = "0.00005";
 const CLIENT_STATE_SEED: &[u8] = b"CLIENT_STATE";
-pub const TICKET_BTC_SATOSHIS: u64 = 5_000; // 0.00005 BTC = 5,000 satoshi
+pub const DEFAULT_TICKET_BTC_SATOSHIS: u64 = 5_000; // 0.00005 BTC = 5,000 satoshi, until the authority reprices
 const BTC_DECIMALS: u8 = 8;
+const SOL_DECIMALS: u8 = 9;
 const USD_DECIMALS: u8 = 6;
 const SECONDS_IN_DAY: i64 = 86400;
 const NY_OFFSET: i64 = 4 * 3600; // UTC-4
 const ROUND_DURATION: i64 = 600; // 10 minutes
+pub const ROUND_LIVENESS_TIMEOUT: i64 = SECONDS_IN_DAY; // after end_time, anyone can cancel a round that never completed
+pub const MAX_ENTRANCE_FEE_BPS: u16 = 2_000; // 20%
+pub const MAX_BENEFICIARIES: usize = 5;
+pub const MAX_COMMISSION_RECIPIENTS: usize = 2 * MAX_BENEFICIARIES; // current and former beneficiaries with a balance
+pub const QUOTE_VALIDITY_SLOTS: u64 = 150; // ~60 seconds
+pub const MAX_QUOTE_DEVIATION_BPS: u16 = 500; // 5%
+
+// Pause scopes, combined into the RaffleState::paused bitmask
+pub const PAUSE_PURCHASES: u8 = 1 << 0;
+pub const PAUSE_ROUND_CREATION: u8 = 1 << 1;
+pub const PAUSE_RANDOMNESS: u8 = 1 << 2;
+pub const PAUSE_CLAIMS: u8 = 1 << 3;
+pub const PAUSE_WITHDRAWALS: u8 = 1 << 4;
+pub const PAUSE_ALL: u8 = PAUSE_PURCHASES
+    | PAUSE_ROUND_CREATION
+    | PAUSE_RANDOMNESS
+    | PAUSE_CLAIMS
+    | PAUSE_WITHDRAWALS;
+
+// Roles, combined into RoleMember::roles; the RaffleState authority holds all of them
+pub const ROLE_OPERATOR: u8 = 1 << 0; // rounds and winner setting
+pub const ROLE_TREASURER: u8 = 1 << 1; // vault withdrawals
+pub const ROLE_PAUSER: u8 = 1 << 2; // pause and unpause
+pub const ROLE_ORACLE_ADMIN: u8 = 1 << 3; // price feeds, supported tokens and ticket pricing
+pub const ROLE_ALL: u8 = ROLE_OPERATOR | ROLE_TREASURER | ROLE_PAUSER | ROLE_ORACLE_ADMIN;
+pub const MAX_ROLE_MEMBERS: usize = 10;
+
+// Timelock for fees, beneficiaries, oracle feeds and vault withdrawals
+pub const DEFAULT_TIMELOCK_DELAY: i64 = SECONDS_IN_DAY;
+pub const MAX_TIMELOCK_DELAY: i64 = 30 * SECONDS_IN_DAY;
+pub const MAX_PENDING_CHANGES: usize = 10;
+
+// Multisig approvals for vault and commission withdrawals
+pub const MAX_MULTISIG_SIGNERS: usize = 10;
+pub const MAX_PROPOSAL_LIFETIME: i64 = 7 * SECONDS_IN_DAY;
+
+// Reserves that vault withdrawals must leave behind
+pub const DEFAULT_RENT_RESERVE_ROUNDS: u32 = 3; // Round + RoundTickets rent
+pub const DEFAULT_VRF_RESERVE_REQUESTS: u32 = 3; // ORAO request fees
 
 #[program]
 pub mod raffle {
@@ -54,14 +104,18 @@ pub mod raffle {
         admin::get_token_raffle(ctx)
     }
 
-    pub fn get_ticket_price_in_btc(ctx: Context<EmptyAccounts>) -> Result<u64> {
+    pub fn get_ticket_price_in_btc(ctx: Context<GetTockenRaffle>) -> Result<u64> {
         admin::get_ticket_price_in_btc(ctx)
     }
 
-    pub fn get_ticket_price(ctx: Context<GetTicketPrice>) -> Result<u64> {
+    pub fn get_ticket_price(ctx: Context<GetTicketPrice>) -> Result<TicketPriceView> {
         admin::get_ticket_price(ctx)
     }
 
+    pub fn get_ticket_quote(ctx: Context<GetTicketPrice>, count: u32) -> Result<TicketQuote> {
+        admin::get_ticket_quote(ctx, count)
+    }
+
     pub fn get_raffle_round_result(ctx: Context<GetRoundAccounts>, round_id: u32) -> Result<RoundResultView> {
         admin::get_raffle_round_result(ctx, round_id)
     }
@@ -78,6 +132,26 @@ pub mod raffle {
         admin::get_raffle_round_count(ctx)
     }
 
+    pub fn get_solvency(ctx: Context<GetSolvency>) -> Result<SolvencyView> {
+        solvency::get_solvency(ctx)
+    }
+
+    pub fn get_solvency_spl(ctx: Context<GetSolvencySpl>) -> Result<SolvencyView> {
+        solvency::get_solvency_spl(ctx)
+    }
+
+    /// Only authority
+    /// Credits up to `max_amount` of the surplus of `sol_vault` over its obligations to the treasury ledger.
+    pub fn reconcile(ctx: Context<Reconcile>, max_amount: u64) -> Result<()> {
+        solvency::reconcile(ctx, max_amount)
+    }
+
+    /// Only authority
+    /// Credits up to `max_amount` of the surplus of a token vault over its obligations to the treasury ledger.
+    pub fn reconcile_spl(ctx: Context<ReconcileSpl>, max_amount: u64) -> Result<()> {
+        solvency::reconcile_spl(ctx, max_amount)
+    }
+
     //TEST
     pub fn get_cumulative_tickets(
         ctx: Context<GetCumulativeTickets>,
@@ -102,18 +176,35 @@ pub mod raffle {
 
     pub fn initialize_raffle(
         ctx: Context<InitializeRaffle>,
-        entrance_fee_percentage: u8,
-        beneficiary: Pubkey,
+        entrance_fee_bps: u16,
+        beneficiaries: Vec<BeneficiaryShare>,
+        btc_oracle: AssetOracle,
+        sol_oracle: AssetOracle,
     ) -> Result<()> {
+        validate_entrance_fee(entrance_fee_bps)?;
+        validate_beneficiaries(&beneficiaries)?;
+        btc_oracle.validate()?;
+        sol_oracle.validate()?;
         
         let raffle_state = &mut ctx.accounts.raffle_state;
         raffle_state.authority = ctx.accounts.authority.key();
-        raffle_state.entrance_fee_percentage = entrance_fee_percentage;
-        raffle_state.beneficiary = beneficiary;
+        raffle_state.entrance_fee_bps = entrance_fee_bps;
+        raffle_state.beneficiaries = beneficiaries;
         raffle_state.created_at = Clock::get()?.unix_timestamp;
         raffle_state.vrf_request_counter = 0;
         raffle_state.bump = ctx.bumps.raffle_state;
         raffle_state.test_ticket_price = None;
+        raffle_state.btc_oracle = btc_oracle;
+        raffle_state.sol_oracle = sol_oracle;
+        raffle_state.price_lock_enabled = false;
+        raffle_state.paused = 0;
+        raffle_state.pending_authority = None;
+        raffle_state.timelock_delay = DEFAULT_TIMELOCK_DELAY;
+        raffle_state.rent_reserve_rounds = DEFAULT_RENT_RESERVE_ROUNDS;
+        raffle_state.vrf_reserve_requests = DEFAULT_VRF_RESERVE_REQUESTS;
+        raffle_state.vault_top_up_bps = 0;
+        raffle_state.rent_vault_target = 0;
+        raffle_state.vrf_vault_target = 0;
 
         msg!("RaffleState initialized successfully");
         msg!("Raffle State PDA: {}", raffle_state.key());
@@ -126,6 +217,15 @@ pub mod raffle {
         
         sol_raffle.authority = ctx.accounts.authority.key();
         sol_raffle.total_rounds = 0;
+        sol_raffle.ticket_denomination = TicketDenomination::DEFAULT;
+        sol_raffle.price_guard = PriceGuard::DEFAULT;
+        sol_raffle.last_ticket_price = None;
+        sol_raffle.last_ticket_price_at = 0;
+        sol_raffle.circuit_breaker_tripped = false;
+        sol_raffle.accrued_commission = 0;
+        sol_raffle.commission_balances = Vec::new();
+        sol_raffle.unassigned_commission = 0;
+        sol_raffle.outstanding_obligations = 0;
         sol_raffle.bump = ctx.bumps.sol_raffle;
 
         msg!("TokenRaffle initialized successfully");
@@ -134,6 +234,43 @@ pub mod raffle {
         Ok(())
     }
 
+    pub fn initialize_token_raffle(ctx: Context<InitializeTokenRaffle>) -> Result<()> {
+        spl::initialize_token_raffle(ctx)
+    }
+
+    /// Only authority or oracle admin
+    pub fn add_supported_token(
+        ctx: Context<AddSupportedToken>,
+        oracle: AssetOracle,
+        min_tickets_per_purchase: u32,
+        max_tickets_per_purchase: u32,
+    ) -> Result<()> {
+        registry::add_supported_token(ctx, oracle, min_tickets_per_purchase, max_tickets_per_purchase)
+    }
+
+    /// Only authority or oracle admin
+    pub fn update_supported_token(
+        ctx: Context<UpdateSupportedToken>,
+        oracle: AssetOracle,
+        min_tickets_per_purchase: u32,
+        max_tickets_per_purchase: u32,
+    ) -> Result<()> {
+        registry::update_supported_token(ctx, oracle, min_tickets_per_purchase, max_tickets_per_purchase)
+    }
+
+    /// Only authority or oracle admin
+    pub fn set_supported_token_enabled(
+        ctx: Context<UpdateSupportedToken>,
+        enabled: bool,
+    ) -> Result<()> {
+        registry::set_supported_token_enabled(ctx, enabled)
+    }
+
+    /// Only authority or oracle admin
+    pub fn remove_supported_token(ctx: Context<RemoveSupportedToken>) -> Result<()> {
+        registry::remove_supported_token(ctx)
+    }
+
     pub fn initialize_client_state(ctx: Context<InitializeClientState>) -> Result<()> {
         vrf::initialize_client_state(ctx)
     }
@@ -163,28 +300,6 @@ pub mod raffle {
         Ok(())
     }
 
-    /// Only authority
-    pub fn withdraw_vrf_vault(
-        ctx: Context<WithdrawVrfVault>,
-        amount: u64,
-    ) -> Result<()> {
-        require!(amount > 0, RaffleError::InvalidAmount);
-        
-        let vault_balance = ctx.accounts.vrf_fee_vault.lamports();
-        require!(
-            vault_balance >= amount,
-            RaffleError::InsufficientFunds
-        );
-        
-        let vrf_vault_bump = ctx.bumps.vrf_fee_vault;
-        
-        **ctx.accounts.vrf_fee_vault.to_account_info().try_borrow_mut_lamports()? -= amount;
-        **ctx.accounts.authority.to_account_info().try_borrow_mut_lamports()? += amount;
-        
-        msg!("Withdrawn {} lamports from VRF vault", amount);
-        Ok(())
-    }
-
     pub fn fund_rent_vault(
         ctx: Context<FundRentVault>,
         amount: u64,
@@ -210,173 +325,63 @@ pub mod raffle {
         Ok(())
     }
 
-    /// Only authority
-    pub fn withdraw_rent_vault(
-        ctx: Context<WithdrawRentVault>,
-        amount: u64,
-    ) -> Result<()> {
-        require!(amount > 0, RaffleError::InvalidAmount);
-        
-        let vault_balance = ctx.accounts.rent_vault.lamports();
-        require!(
-            vault_balance >= amount,
-            RaffleError::InsufficientFunds
-        );
-        
-        let vrf_vault_bump = ctx.bumps.rent_vault;
-        
-        **ctx.accounts.rent_vault.to_account_info().try_borrow_mut_lamports()? -= amount;
-        **ctx.accounts.authority.to_account_info().try_borrow_mut_lamports()? += amount;
-        
-        msg!("Withdrawn {} lamports from VRF vault", amount);
-        Ok(())
+    /// Only authority, or the role the action requires (see `TimelockedAction::required_role`)
+    /// Changes fees, beneficiaries, price feeds, vault reserve and top-up settings or the
+    /// delay itself once `RaffleState::timelock_delay` has elapsed.
+    pub fn queue_change(ctx: Context<QueueChange>, action: TimelockedAction) -> Result<()> {
+        timelock::queue_change(ctx, action)
     }
 
-//TEST
-pub fn initialize_round(
-    ctx: Context<InitializeRound>,
-    round_id: u32,
-) -> Result<()> {
-    let clock = Clock::get()?;
-    let rent = Rent::get()?;
-    let current_time = clock.unix_timestamp;
-    let round_end_time = get_temporary_close_time(current_time);
-
-    let sol_raffle = &mut ctx.accounts.sol_raffle;
-    let sol_raffle_key = sol_raffle.key();
-    
-    // Bumps
-    let vault_bump = ctx.bumps.rent_vault;
-    let round_bump = ctx.bumps.round;
-    let round_tickets_bump = ctx.bumps.round_tickets;
-    
-    let vault_seeds = &[b"rent_vault".as_ref(), &[vault_bump]];
-    
-    // ========== 1. Create Round account ==========
-    let round_seeds = &[
-        b"round".as_ref(),
-        sol_raffle_key.as_ref(),
-        &round_id.to_le_bytes(),
-        &[round_bump],
-    ];
-    let round_space = 8 + Round::INIT_SPACE;
-    let round_lamports = rent.minimum_balance(round_space);
-
-    invoke_signed(
-        &system_instruction::create_account(
-            &ctx.accounts.rent_vault.key(),
-            &ctx.accounts.round.key(),
-            round_lamports,
-            round_space as u64,
-            ctx.program_id,
-        ),
-        &[
-            ctx.accounts.rent_vault.to_account_info(),
-            ctx.accounts.round.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-        ],
-        &[vault_seeds, round_seeds],
-    )?;
-    
-    // Initialize Round
-    {
-        let mut round_account_data = ctx.accounts.round.try_borrow_mut_data()?;
-        
-        let round_data = Round {
-            initialized: true,
-            token_raffle: sol_raffle_key,
-            round_id,
-            status: RoundStatus::Open,
-            start_time: current_time,
-            end_time: round_end_time,
-            prize_amount: 0,
-            commission_balance: 0,
-            purchases_count: 0,
-            total_tickets: 0,
-            winner_ticket_index: None,
-            winner_purchase_index: None,
-            winner_address: None,
-            prize_claimed: false,
-            bump: round_bump,
-        };
-        
-        let mut writer = &mut round_account_data[..];
-        round_data.try_serialize(&mut writer)?;
+    /// Only authority
+    pub fn cancel_change(ctx: Context<CancelChange>, change_id: u64) -> Result<()> {
+        timelock::cancel_change(ctx, change_id)
     }
 
-    // ========== 2. Create RoundTickets account ==========
-    let round_key = ctx.accounts.round.key();
-    let round_tickets_seeds = &[
-        b"round_tickets".as_ref(),
-        round_key.as_ref(),
-        &[round_tickets_bump],
-    ];
-    let tickets_space = 8 + std::mem::size_of::<RoundTickets>();
-    let tickets_lamports = rent.minimum_balance(tickets_space);
-    
-    invoke_signed(
-        &system_instruction::create_account(
-            &ctx.accounts.rent_vault.key(),
-            &ctx.accounts.round_tickets.key(),
-            tickets_lamports,
-            tickets_space as u64,
-            ctx.program_id,
-        ),
-        &[
-            ctx.accounts.rent_vault.to_account_info(),
-            ctx.accounts.round_tickets.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-        ],
-        &[vault_seeds, round_tickets_seeds],
-    )?;
-
-    // Initialize RoundTickets
-    {
-        let mut tickets_data = ctx.accounts.round_tickets.try_borrow_mut_data()?;
-        let discriminator = RoundTickets::DISCRIMINATOR;
-        tickets_data[0..8].copy_from_slice(&discriminator);
-        
-        for byte in &mut tickets_data[8..] {
-            *byte = 0;
-        }
-        
-        tickets_data[8..40].copy_from_slice(round_key.as_ref());
-        
-        let bump_offset = 8 + 32 + (1024 * 4) + (1024 * 4) + 4;
-        tickets_data[bump_offset] = round_tickets_bump;
+    pub fn execute_change(ctx: Context<ExecuteChange>, change_id: u64) -> Result<()> {
+        timelock::execute_change(ctx, change_id)
     }
 
-    msg!(
-        "✅ Round {} and RoundTickets created (paid {} lamports total)", 
-        round_id, 
-        round_lamports + tickets_lamports
-    );
-
-    if let Some(prev_id) = sol_raffle.current_round_id {
-        let prev_round_end = sol_raffle.current_round_end_time.unwrap();
-
-        if current_time >= prev_round_end {
-            
-            if sol_raffle.current_round_status == RoundStatus::Open {
-                if !sol_raffle.pending_rounds.contains(&prev_id) {
-                    sol_raffle.pending_rounds.push(prev_id);
-                    msg!("✅ Added round {} to pending (status: Open)", prev_id);
-                }
-            } else {
-                msg!("⚠️ Round {} already Completed, not adding to pending", prev_id);
-            }
-        }
+    /// Only authority
+    pub fn initialize_pending_changes(ctx: Context<InitializePendingChanges>) -> Result<()> {
+        timelock::initialize_pending_changes(ctx)
     }
 
-    sol_raffle.current_round_id = Some(round_id);
-    sol_raffle.total_rounds = sol_raffle
-        .total_rounds
-        .checked_add(1)
-        .ok_or(ProgramError::ArithmeticOverflow)?;
-    sol_raffle.current_round_status = RoundStatus::Open;
-    sol_raffle.current_round_end_time = Some(round_end_time);
+    pub fn get_pending_changes(ctx: Context<GetPendingChanges>) -> Result<Vec<PendingChange>> {
+        timelock::get_pending_changes(ctx)
+    }
 
-    Ok(())
+//TEST
+/// Only authority or operator
+pub fn initialize_round(
+    ctx: Context<InitializeRound>,
+    round_id: u32,
+) -> Result<()> {
+    ctx.accounts.raffle_state.require_not_paused(PAUSE_ROUND_CREATION)?;
+
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let round_info = ctx.accounts.round.to_account_info();
+    let round_tickets_info = ctx.accounts.round_tickets.to_account_info();
+    let rent_vault_info = ctx.accounts.rent_vault.to_account_info();
+    let system_program_info = ctx.accounts.system_program.to_account_info();
+
+    create_round(
+        ctx.program_id,
+        &mut ctx.accounts.sol_raffle,
+        &ctx.accounts.raffle_state,
+        NewRoundAccounts {
+            round: &round_info,
+            round_tickets: &round_tickets_info,
+            rent_vault: &rent_vault_info,
+            system_program: &system_program_info,
+            round_bump: ctx.bumps.round,
+            round_tickets_bump: ctx.bumps.round_tickets,
+            rent_vault_bump: ctx.bumps.rent_vault,
+        },
+        round_id,
+        current_time,
+        None,
+    )
 }
 //TEST
 
@@ -387,347 +392,70 @@ pub fn buy_tickets_sol(
     count: u32,
     max_cost: u64,
 ) -> Result<()> {
-    msg!("Tickets count: {}", count);
-    require!(count > 0, RaffleError::InvalidTicketCount);
-
-    let clock = Clock::get()?;
-    let current_time = clock.unix_timestamp;
-    let sol_raffle = &mut ctx.accounts.sol_raffle;
-    
-    let round_exists = ctx.accounts.round.owner == ctx.program_id 
-        && ctx.accounts.round.data_len() > 0;
-    
-    if !round_exists {
-        msg!("🆕 Round {} doesn't exist, creating...", round_id);
-        
-        let rent = Rent::get()?;
-        let sol_raffle_key = sol_raffle.key();
-        
-        let vault_bump = ctx.bumps.rent_vault;
-        let round_bump = ctx.bumps.round;
-        let round_tickets_bump = ctx.bumps.round_tickets;
-        
-        let vault_seeds = &[b"rent_vault".as_ref(), &[vault_bump]];
-        
-        // ========== Create Round account ==========
-        let round_seeds = &[
-            b"round".as_ref(),
-            sol_raffle_key.as_ref(),
-            &round_id.to_le_bytes(),
-            &[round_bump],
-        ];
-        let round_space = 8 + Round::INIT_SPACE;
-        let round_lamports = rent.minimum_balance(round_space);
-
-        invoke_signed(
-            &system_instruction::create_account(
-                &ctx.accounts.rent_vault.key(),
-                &ctx.accounts.round.key(),
-                round_lamports,
-                round_space as u64,
-                ctx.program_id,
-            ),
-            &[
-                ctx.accounts.rent_vault.to_account_info(),
-                ctx.accounts.round.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-            &[vault_seeds, round_seeds],
-        )?;
-        
-        // Initialize Round
-        {
-            let round_end_time = get_temporary_close_time(current_time);
-            let mut round_account_data = ctx.accounts.round.try_borrow_mut_data()?;
-            
-            let round_data = Round {
-                initialized: true,
-                token_raffle: sol_raffle_key,
-                round_id,
-                status: RoundStatus::Open,
-                start_time: current_time,
-                end_time: round_end_time,
-                prize_amount: 0,
-                commission_balance: 0,
-                purchases_count: 0,
-                total_tickets: 0,
-                winner_ticket_index: None,
-                winner_purchase_index: None,
-                winner_address: None,
-                prize_claimed: false,
-                bump: round_bump,
-            };
-            
-            let mut writer = &mut round_account_data[..];
-            round_data.try_serialize(&mut writer)?;
-        }
+    process_sol_purchase(ctx, round_id, count, max_cost, None)
+}
 
-        // ========== Create RoundTickets account ==========
-        let round_key = ctx.accounts.round.key();
-        let round_tickets_seeds = &[
-            b"round_tickets".as_ref(),
-            round_key.as_ref(),
-            &[round_tickets_bump],
-        ];
-        let tickets_space = 8 + std::mem::size_of::<RoundTickets>();
-        let tickets_lamports = rent.minimum_balance(tickets_space);
-        
-        invoke_signed(
-            &system_instruction::create_account(
-                &ctx.accounts.rent_vault.key(),
-                &ctx.accounts.round_tickets.key(),
-                tickets_lamports,
-                tickets_space as u64,
-                ctx.program_id,
-            ),
-            &[
-                ctx.accounts.rent_vault.to_account_info(),
-                ctx.accounts.round_tickets.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-            &[vault_seeds, round_tickets_seeds],
-        )?;
+/// Buys `quote.count` tickets at the price returned by `get_ticket_quote`.
+/// The quote is caller-supplied, so besides the deviation check the cost is
+/// hard-capped at `quote.total_cost` plus `max_deviation_bps`, and its expiry is
+/// derived from its oracle slots rather than taken from `expiry_slot`.
+pub fn buy_tickets_sol_with_quote(
+    ctx: Context<BuyTicketsSol>,
+    round_id: u32,
+    purchase_index: u32,
+    quote: TicketQuote,
+    max_deviation_bps: u16,
+) -> Result<()> {
+    require!(
+        max_deviation_bps <= MAX_QUOTE_DEVIATION_BPS,
+        RaffleError::QuoteDeviationTooHigh
+    );
+    let max_cost = u64::try_from(
+        (quote.total_cost as u128)
+            .checked_mul((BPS_DENOMINATOR + max_deviation_bps) as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR as u128,
+    )
+    .map_err(|_| ProgramError::ArithmeticOverflow)?;
+    process_sol_purchase(ctx, round_id, quote.count, max_cost, Some((quote, max_deviation_bps)))
+}
 
-        // Initialize RoundTickets
-        {
-            let mut tickets_data = ctx.accounts.round_tickets.try_borrow_mut_data()?;
-        
-            let discriminator = RoundTickets::DISCRIMINATOR;
-            tickets_data[0..8].copy_from_slice(&discriminator);
-            
-            for byte in &mut tickets_data[8..] {
-                *byte = 0;
-            }
-            
-            tickets_data[8..40].copy_from_slice(round_key.as_ref());
-            
-            let bump_offset = 8 + 32 + (1024 * 4) + (1024 * 4) + 4;
-            tickets_data[bump_offset] = round_tickets_bump;
-        }
+    pub fn buy_tickets_spl(
+        ctx: Context<BuyTicketsSpl>,
+        round_id: u32,
+        purchase_index: u32,
+        count: u32,
+        max_cost: u64,
+    ) -> Result<()> {
+        spl::buy_tickets_spl(ctx, round_id, purchase_index, count, max_cost)
+    }
 
-        // Update sol_raffle state
-        if let Some(prev_id) = sol_raffle.current_round_id {
-            let prev_round_end = sol_raffle.current_round_end_time.unwrap();
+    pub fn request_randomness<'info>(
+        ctx: Context<'_, '_, '_, 'info, RequestRandomness<'info>>,
+        seeds: Vec<[u8; 32]>,
+    ) -> Result<()> {
 
-            if current_time >= prev_round_end {
-                if sol_raffle.current_round_status == RoundStatus::Open {
-                    if !sol_raffle.pending_rounds.contains(&prev_id) {
-                        sol_raffle.pending_rounds.push(prev_id);
-                        msg!("✅ Added round {} to pending", prev_id);
-                    }
-                }
-            }
-        }
+        ctx.accounts.raffle_state.require_not_paused(PAUSE_RANDOMNESS)?;
 
-        sol_raffle.current_round_id = Some(round_id);
-        sol_raffle.total_rounds = sol_raffle
-            .total_rounds
-            .checked_add(1)
-            .ok_or(ProgramError::ArithmeticOverflow)?;
-        sol_raffle.current_round_status = RoundStatus::Open;
-        sol_raffle.current_round_end_time = Some(get_temporary_close_time(current_time));
-        
-        msg!("✅ Round {} created", round_id);
-    }
-    
-    // Load and deserialize Round
-    let round_data = ctx.accounts.round.try_borrow_data()?;
-    let mut round_reader = &round_data[..];
-    let mut round = Round::try_deserialize(&mut round_reader)?;
-    drop(round_data); // Release the borrow immediately
+        require_eq!(
+            ctx.remaining_accounts.len(),
+            seeds.len(),
+            RaffleError::SeedMismatch
+        );
 
-    let current_round_id = sol_raffle.current_round_id.unwrap_or(0);
-    let current_round_end_time = sol_raffle.current_round_end_time.unwrap_or(i64::MAX);
+        let sol_raffle = &mut ctx.accounts.sol_raffle;
 
-    let is_current_round = round_id == current_round_id && current_time < current_round_end_time;
-    let is_next_round = round_id == current_round_id + 1 && current_time >= current_round_end_time;
+        let round_id = determine_round_to_process(sol_raffle)?;
+        msg!("round_id for winner pick: {}", round_id);
 
-    require!(
-        is_current_round || is_next_round,
-        RaffleError::RoundNotAvailable
-    );
+        //Checking for available request_accounts to use
+        let raffle_state = &mut ctx.accounts.raffle_state;
+        let idx = raffle_state.vrf_request_counter as usize;
+        msg!("idx: {}", idx);
+        msg!("seeds.len(): {}", seeds.len());
+        require!(idx < seeds.len(), RaffleError::AllRequestsCompleted);
 
-    require!(round.initialized, RaffleError::RoundNotInitialized);
-
-    // Verify round_tickets connection
-    {
-        let tickets_data = ctx.accounts.round_tickets.try_borrow_data()?;
-        let tickets_round_key = Pubkey::try_from(&tickets_data[8..40])
-            .map_err(|_| RaffleError::RoundTicketsNotInitialized)?;
-        require!(
-            tickets_round_key == ctx.accounts.round.key(),
-            RaffleError::RoundTicketsNotInitialized
-        );
-    }
-
-    let round_tickets_purchase = &mut ctx.accounts.round_tickets_purchase;
-    let round_tickets_purchase_bump = ctx.bumps.round_tickets_purchase;
-
-    initialize_round_tickets_purchase(
-        round_tickets_purchase,
-        ctx.accounts.round.key(),
-        round.purchases_count,
-        ctx.accounts.player.key(),
-        count,
-        round_tickets_purchase_bump
-    )?;
-
-    let raffle_state = &ctx.accounts.raffle_state;
-    let ticket_price: u64 = if let Some(test_price) = raffle_state.test_ticket_price {
-        msg!("Using test ticket price: {} lamports", test_price);
-        test_price
-    } else {
-        msg!("Calculating ticket price from oracle feeds");
-        calculate_ticket_price_for_sol(
-            &ctx.accounts.btc_price_feed,
-            &ctx.accounts.sol_price_feed,
-        )?
-    };
-    require!(ticket_price > 0, RaffleError::InvalidTicketPrice);
-
-    let cost = ticket_price
-        .checked_mul(count as u64)
-        .ok_or(ProgramError::ArithmeticOverflow)?;
-    require!(cost <= max_cost, RaffleError::InsufficientSlippage);
-
-    require!(
-        ctx.accounts.player.lamports() >= cost,
-        RaffleError::InsufficientFunds
-    );
-
-    let commission_amount = cost
-        .checked_mul(raffle_state.entrance_fee_percentage as u64)
-        .ok_or(ProgramError::ArithmeticOverflow)?
-        .checked_div(100)
-        .ok_or(ProgramError::ArithmeticOverflow)?;
-
-    round.commission_balance = round
-        .commission_balance
-        .checked_add(commission_amount)
-        .ok_or(ProgramError::ArithmeticOverflow)?;
-
-    let prize_amount = cost
-        .checked_sub(commission_amount)
-        .ok_or(ProgramError::ArithmeticOverflow)?;
-
-    round.prize_amount = round
-        .prize_amount
-        .checked_add(prize_amount)
-        .ok_or(ProgramError::ArithmeticOverflow)?;
-
-    let is_first_buyer = round.total_tickets == 0;
-
-    let mut new_total = round
-        .total_tickets
-        .checked_add(count)
-        .ok_or(ProgramError::ArithmeticOverflow)?;
-
-    anchor_lang::system_program::transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            anchor_lang::system_program::Transfer {
-                from: ctx.accounts.player.to_account_info(),
-                to: ctx.accounts.vault.to_account_info(),
-            },
-        ),
-        cost,
-    )?;
-
-    if is_first_buyer {
-        new_total = new_total + 1;
-
-        round_tickets_purchase.tickets_count = round_tickets_purchase
-            .tickets_count
-            .checked_add(1)
-            .ok_or(ProgramError::ArithmeticOverflow)?;
-
-        emit!(FirstTicketBonusAwarded {
-            token: sol_raffle.token_mint,
-            round_id: round.round_id,
-            buyer: ctx.accounts.player.key(),
-            timestamp: current_time,
-            round_start_time: round.start_time,
-            round_end_time: round.end_time,
-        });
-    }
-
-    // Update round_tickets using zero-copy mutation
-    {
-        let mut tickets_data = ctx.accounts.round_tickets.try_borrow_mut_data()?;
-        
-        // Read current len (offset: 8 + 32 + 4096 + 4096 = 8232)
-        let len_offset = 8 + 32 + (1024 * 4) + (1024 * 4);
-        let len = u32::from_le_bytes([
-            tickets_data[len_offset],
-            tickets_data[len_offset + 1],
-            tickets_data[len_offset + 2],
-            tickets_data[len_offset + 3],
-        ]) as usize;
-        
-        // Write new cumulative value
-        let cumulative_bytes = new_total.to_le_bytes();
-        if len < 1024 {
-            // Write to cumulative_tickets_1 (offset: 8 + 32)
-            let ticket_offset = 8 + 32 + (len * 4);
-            tickets_data[ticket_offset..ticket_offset + 4].copy_from_slice(&cumulative_bytes);
-        } else {
-            // Write to cumulative_tickets_2 (offset: 8 + 32 + 4096)
-            let ticket_offset = 8 + 32 + (1024 * 4) + ((len - 1024) * 4);
-            tickets_data[ticket_offset..ticket_offset + 4].copy_from_slice(&cumulative_bytes);
-        }
-        
-        // Update len
-        let new_len = (len + 1) as u32;
-        tickets_data[len_offset..len_offset + 4].copy_from_slice(&new_len.to_le_bytes());
-    }
-
-    round.total_tickets = new_total;
-    round.purchases_count += 1;
-
-    {
-        let mut final_round_data = ctx.accounts.round.try_borrow_mut_data()?;
-        let mut writer = &mut final_round_data[..];
-        round.try_serialize(&mut writer)?;
-    }
-
-    emit!(TicketPurchased {
-        token: sol_raffle.token_mint,
-        round_id: round.round_id,
-        buyer: ctx.accounts.player.key(),
-        count,
-        total_amount: cost,
-        prize_amount,
-        commission_amount,
-        timestamp: current_time,
-    });
-
-    Ok(())
-}
-
-    pub fn request_randomness<'info>(
-        ctx: Context<'_, '_, '_, 'info, RequestRandomness<'info>>,
-        seeds: Vec<[u8; 32]>,
-    ) -> Result<()> {
-
-        require_eq!(
-            ctx.remaining_accounts.len(),
-            seeds.len(),
-            RaffleError::SeedMismatch
-        );
-
-        let sol_raffle = &mut ctx.accounts.sol_raffle;
-
-        let round_id = determine_round_to_process(sol_raffle)?;
-        msg!("round_id for winner pick: {}", round_id);
-
-        //Checking for available request_accounts to use
-        let raffle_state = &mut ctx.accounts.raffle_state;
-        let idx = raffle_state.vrf_request_counter as usize;
-        msg!("idx: {}", idx);
-        msg!("seeds.len(): {}", seeds.len());
-        require!(idx < seeds.len(), RaffleError::AllRequestsCompleted);
-
-        let cpi_program = ctx.accounts.vrf.to_account_info();
+        let cpi_program = ctx.accounts.vrf.to_account_info();
 
         let vrf_fee_vault_bump = ctx.bumps.vrf_fee_vault;
         
@@ -768,10 +496,7 @@ pub fn buy_tickets_sol(
         
         callback = callback.with_remaining_account(RemainingAccount::writable(
             sol_raffle_key,
-            vec![
-                b"sol_raffle".to_vec(),
-                vec![sol_raffle.bump],
-            ],
+            sol_raffle.pda_seeds(),
         ));
         
         callback = callback.with_remaining_account(RemainingAccount::writable(
@@ -803,6 +528,20 @@ pub fn buy_tickets_sol(
             ],
         ));
 
+        // SOL rounds top up rent_vault and vrf_fee_vault from their commission on settlement
+        if sol_raffle.is_sol() {
+            callback = callback.with_remaining_account(RemainingAccount::readonly(raffle_state.key()));
+
+            for vault_seed in [b"sol_vault".as_ref(), b"rent_vault", b"vrf_fee_vault"] {
+                let (vault_pubkey, vault_bump) =
+                    Pubkey::find_program_address(&[vault_seed], &crate::id());
+                callback = callback.with_remaining_account(RemainingAccount::writable(
+                    vault_pubkey,
+                    vec![vault_seed.to_vec(), vec![vault_bump]],
+                ));
+            }
+        }
+
         let mut cpi_accounts = cpi::accounts::Request {
             payer: ctx.accounts.vrf_fee_vault.to_account_info(),
             state: ctx.accounts.client_state.to_account_info(),
@@ -846,6 +585,7 @@ pub fn buy_tickets_sol(
     }
 
     pub fn claim_prize_sol(ctx: Context<ClaimPrizeSol>, round_id: u32) -> Result<()> {
+        ctx.accounts.raffle_state.require_not_paused(PAUSE_CLAIMS)?;
 
         let sol_raffle = &mut ctx.accounts.sol_raffle;
         let round = &mut ctx.accounts.round;
@@ -861,46 +601,30 @@ pub fn buy_tickets_sol(
         );
         require!(!round.prize_claimed, RaffleError::PrizeAlreadyClaimed);
 
+        // Commission was accrued to the treasury ledger when the round completed
         let prize_amount = round.prize_amount;
-        let commission_amount = round.commission_balance;
-        let total_required = prize_amount
-            .checked_add(commission_amount)
-            .ok_or(ProgramError::ArithmeticOverflow)?;
         msg!("prize_amount: {}", prize_amount);
-        msg!("commission_amount: {}", commission_amount);
-        msg!("total_required: {}", total_required);
 
         let vault_balance = **vault.lamports.borrow();
         require!(
-            vault_balance >= total_required,
+            vault_balance >= prize_amount,
             RaffleError::InsufficientVaultBalance
         );
         
         let winner_balance_before = **ctx.accounts.winner.lamports.borrow();
-        let beneficiary_balance_before = **ctx.accounts.beneficiary.lamports.borrow();
         msg!(
             "Winner balance before: {}",
             winner_balance_before
         );
-        msg!(
-            "Beneficiary balance before: {}",
-            beneficiary_balance_before
-        );
 
         // Transfer prize to winner
         **ctx.accounts.vault.try_borrow_mut_lamports()? -= prize_amount;
         **ctx.accounts.winner.try_borrow_mut_lamports()? += prize_amount;
+        sol_raffle.settle_obligations(prize_amount)?;
         
         msg!("✓ Transferred {} lamports to winner", prize_amount);
         
-        // Transfer commission to beneficiary
-        **ctx.accounts.vault.try_borrow_mut_lamports()? -= commission_amount;
-        **ctx.accounts.beneficiary.try_borrow_mut_lamports()? += commission_amount;
-        
-        msg!("✓ Transferred {} lamports to beneficiary", commission_amount);
-        
         let winner_balance_after = **ctx.accounts.winner.lamports.borrow();
-        let beneficiary_balance_after = **ctx.accounts.beneficiary.lamports.borrow();
         
         msg!("=== Transfer Completed ===");
         msg!(
@@ -908,11 +632,6 @@ pub fn buy_tickets_sol(
             winner_balance_after,
             prize_amount
         );
-        msg!(
-            "Beneficiary balance after: {} lamports (delta: +{})",
-            beneficiary_balance_after,
-            commission_amount
-        );
 
         round.prize_claimed = true;
         emit!(PrizeClaimed {
@@ -923,11 +642,143 @@ pub fn buy_tickets_sol(
         Ok(())
     }
 
+    /// Only authority, or anyone once `ROUND_LIVENESS_TIMEOUT` has passed since the round ended
+    pub fn cancel_round(ctx: Context<CancelRound>, round_id: u32) -> Result<()> {
+        cancellation::cancel_round(ctx, round_id)
+    }
+
+    pub fn refund_purchase(
+        ctx: Context<RefundPurchase>,
+        round_id: u32,
+        purchase_index: u32,
+    ) -> Result<()> {
+        cancellation::refund_purchase(ctx, round_id, purchase_index)
+    }
+
+    pub fn refund_purchase_spl(
+        ctx: Context<RefundPurchaseSpl>,
+        round_id: u32,
+        purchase_index: u32,
+    ) -> Result<()> {
+        spl::refund_purchase_spl(ctx, round_id, purchase_index)
+    }
+
+    pub fn claim_prize_spl(ctx: Context<ClaimPrizeSpl>, round_id: u32) -> Result<()> {
+        spl::claim_prize_spl(ctx, round_id)
+    }
+
+    /// Executes an approved `WithdrawCommissionSol` proposal: pays its amount out of
+    /// the recipient's accrued SOL commission balance.
+    pub fn withdraw_commission_sol(ctx: Context<WithdrawCommissionSol>, proposal_id: u64) -> Result<()> {
+        ctx.accounts.raffle_state.require_not_paused(PAUSE_WITHDRAWALS)?;
+
+        let proposal = &ctx.accounts.proposal;
+        require_executable(&ctx.accounts.multisig, proposal, Clock::get()?.unix_timestamp)?;
+        let MultisigAction::WithdrawCommissionSol { amount, recipient } = proposal.action else {
+            return err!(RaffleError::ProposalMismatch);
+        };
+        require_keys_eq!(ctx.accounts.recipient.key(), recipient, RaffleError::InvalidRecipient);
+        require!(
+            ctx.accounts.vault.lamports() >= amount,
+            RaffleError::InsufficientVaultBalance
+        );
+
+        let sol_raffle = &mut ctx.accounts.sol_raffle;
+        let recipient_balance = sol_raffle.debit_commission(&recipient, amount)?;
+        sol_raffle.settle_obligations(amount)?;
+
+        **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.recipient.try_borrow_mut_lamports()? += amount;
+
+        msg!("✓ Transferred {} lamports of commission to {}", amount, recipient);
+
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        emit!(CommissionWithdrawn {
+            token: sol_raffle.token_mint,
+            proposal_id,
+            recipient,
+            amount,
+            recipient_balance,
+            accrued_commission: sol_raffle.accrued_commission,
+            timestamp,
+        });
+
+        emit!(ProposalExecuted {
+            proposal_id,
+            action: ctx.accounts.proposal.action.clone(),
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Executes an approved `WithdrawCommissionSpl` proposal.
+    pub fn withdraw_commission_spl(ctx: Context<WithdrawCommissionSpl>, proposal_id: u64) -> Result<()> {
+        spl::withdraw_commission_spl(ctx, proposal_id)
+    }
+
+    /// Only authority
+    pub fn initialize_multisig(
+        ctx: Context<InitializeMultisig>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        multisig::initialize_multisig(ctx, signers, threshold)
+    }
+
+    /// Only a multisig signer, or a treasurer for withdrawals
+    /// `lifetime` is in seconds, up to `MAX_PROPOSAL_LIFETIME`.
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        action: MultisigAction,
+        lifetime: i64,
+    ) -> Result<()> {
+        multisig::create_proposal(ctx, action, lifetime)
+    }
+
+    /// Only a multisig signer
+    pub fn approve_proposal(ctx: Context<ApproveProposal>, proposal_id: u64) -> Result<()> {
+        multisig::approve_proposal(ctx, proposal_id)
+    }
+
+    pub fn cancel_proposal(ctx: Context<CancelProposal>, proposal_id: u64) -> Result<()> {
+        multisig::cancel_proposal(ctx, proposal_id)
+    }
+
+    pub fn execute_vault_withdrawal(
+        ctx: Context<ExecuteVaultWithdrawal>,
+        proposal_id: u64,
+    ) -> Result<()> {
+        multisig::execute_vault_withdrawal(ctx, proposal_id)
+    }
+
+    pub fn execute_signers_update(
+        ctx: Context<ExecuteSignersUpdate>,
+        proposal_id: u64,
+    ) -> Result<()> {
+        multisig::execute_signers_update(ctx, proposal_id)
+    }
+
+    /// Executes an approved `AssignCommission` proposal.
+    pub fn execute_commission_assignment(
+        ctx: Context<ExecuteCommissionAssignment>,
+        proposal_id: u64,
+    ) -> Result<()> {
+        multisig::execute_commission_assignment(ctx, proposal_id)
+    }
+
     pub fn test_calculate_price(ctx: Context<TestCalculatePrice>) -> Result<()> {
+        let raffle_state = &ctx.accounts.raffle_state;
         let price = calculate_ticket_price_for_sol(
+            raffle_state,
+            &TicketDenomination::DEFAULT,
             &ctx.accounts.btc_price_feed,
+            &ctx.accounts.btc_secondary_price_feed,
             &ctx.accounts.sol_price_feed,
-        )?;
+            &ctx.accounts.sol_secondary_price_feed,
+        )?
+        .amount;
 
         msg!("✅ Final ticket price in lamports: {}", price);
         msg!(
@@ -938,98 +789,1075 @@ pub fn buy_tickets_sol(
         Ok(())
     }
 
-    //TEST
-    pub fn set_test_ticket_price(
-        ctx: Context<SetTestTicketPrice>,
-        price: Option<u64>,
+    /// Only authority or oracle admin
+    /// Applies to rounds created afterwards; open rounds keep their denomination.
+    pub fn update_ticket_denomination(
+        ctx: Context<UpdateTokenRaffleSettings>,
+        denomination: TicketDenomination,
     ) -> Result<()> {
-        let raffle_state = &mut ctx.accounts.raffle_state;
-        raffle_state.test_ticket_price = price;
-        
-        if let Some(p) = price {
-            msg!("Test ticket price set to: {} lamports", p);
-        } else {
-            msg!("Test ticket price disabled - using oracle calculation");
-        }
-        
+        denomination.validate()?;
+
+        let token_raffle = &mut ctx.accounts.token_raffle;
+
+        emit!(TicketDenominationUpdated {
+            token: token_raffle.token_mint,
+            old_denomination: token_raffle.ticket_denomination,
+            denomination,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        token_raffle.ticket_denomination = denomination;
+        // Prices in the new denomination are not comparable with the last one
+        token_raffle.last_ticket_price = None;
+
+        msg!("Ticket denomination updated: {:?}", denomination);
+
         Ok(())
     }
-    //TEST
 
-    pub fn set_winner_address(
-        ctx: Context<SetWinnerAddress>,
-        round_id: u32,
-        purchase_index: u32,
+    /// Only authority or oracle admin
+    pub fn update_price_guard(
+        ctx: Context<UpdateTokenRaffleSettings>,
+        price_guard: PriceGuard,
     ) -> Result<()> {
-        let round = &mut ctx.accounts.round;
-        let round_tickets_purchase = &ctx.accounts.round_tickets_purchase;
+        price_guard.validate()?;
 
-        require!(
-            round.status == RoundStatus::Completed,
+        let token_raffle = &mut ctx.accounts.token_raffle;
+
+        emit!(PriceGuardUpdated {
+            token: token_raffle.token_mint,
+            old_price_guard: token_raffle.price_guard,
+            price_guard,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        token_raffle.price_guard = price_guard;
+
+        msg!("Price guard updated: {:?}", price_guard);
+
+        Ok(())
+    }
+
+    /// Only authority or oracle admin
+    /// Resumes purchases; the next accepted price becomes the new reference.
+    pub fn reset_circuit_breaker(ctx: Context<UpdateTokenRaffleSettings>) -> Result<()> {
+        let token_raffle = &mut ctx.accounts.token_raffle;
+        token_raffle.circuit_breaker_tripped = false;
+        token_raffle.last_ticket_price = None;
+
+        msg!("✅ Circuit breaker reset for {}", token_raffle.token_mint);
+
+        emit!(CircuitBreakerReset {
+            token: token_raffle.token_mint,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Only authority or pauser
+    /// Sets the given `PAUSE_*` scopes; scopes already paused stay paused.
+    pub fn pause(ctx: Context<SetPaused>, scopes: u8) -> Result<()> {
+        set_paused(&mut ctx.accounts.raffle_state, scopes, true)
+    }
+
+    /// Only authority or pauser
+    /// Clears the given `PAUSE_*` scopes.
+    pub fn unpause(ctx: Context<SetPaused>, scopes: u8) -> Result<()> {
+        set_paused(&mut ctx.accounts.raffle_state, scopes, false)
+    }
+
+    /// Only authority
+    /// First step of the authority transfer; takes effect once `new_authority` accepts.
+    pub fn propose_authority(
+        ctx: Context<UpdateRaffleSettings>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let raffle_state = &mut ctx.accounts.raffle_state;
+        require_keys_neq!(new_authority, Pubkey::default(), RaffleError::InvalidAuthority);
+        require_keys_neq!(new_authority, raffle_state.authority, RaffleError::InvalidAuthority);
+
+        raffle_state.pending_authority = Some(new_authority);
+
+        msg!("Authority transfer proposed: {} -> {}", raffle_state.authority, new_authority);
+
+        emit!(AuthorityTransferProposed {
+            authority: raffle_state.authority,
+            pending_authority: new_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Only authority
+    pub fn cancel_authority_transfer(ctx: Context<UpdateRaffleSettings>) -> Result<()> {
+        let raffle_state = &mut ctx.accounts.raffle_state;
+        let pending_authority = raffle_state
+            .pending_authority
+            .take()
+            .ok_or(RaffleError::NoPendingAuthority)?;
+
+        msg!("Authority transfer to {} cancelled", pending_authority);
+
+        emit!(AuthorityTransferCancelled {
+            authority: raffle_state.authority,
+            pending_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Only pending authority
+    /// Moves RaffleState, ClientState and the SOL raffle to the new authority, together with
+    /// every SPL TokenRaffle passed as a writable remaining account. Raffles left out can be
+    /// aligned later with `sync_token_raffle_authority`.
+    pub fn accept_authority<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AcceptAuthority<'info>>,
+    ) -> Result<()> {
+        let new_authority = ctx.accounts.new_authority.key();
+        let raffle_state = &mut ctx.accounts.raffle_state;
+        let old_authority = raffle_state.authority;
+
+        raffle_state.authority = new_authority;
+        raffle_state.pending_authority = None;
+        ctx.accounts.client_state.authority = new_authority;
+        ctx.accounts.sol_raffle.authority = new_authority;
+
+        for token_raffle_info in ctx.remaining_accounts.iter() {
+            let mut token_raffle = Account::<TokenRaffle>::try_from(token_raffle_info)?;
+            require_keys_eq!(
+                token_raffle.key(),
+                token_raffle.address()?,
+                RaffleError::InvalidTokenRaffle
+            );
+            token_raffle.authority = new_authority;
+            token_raffle.exit(&crate::ID)?;
+
+            msg!("TokenRaffle {} moved to the new authority", token_raffle.token_mint);
+        }
+
+        msg!("✅ Authority transferred: {} -> {}", old_authority, new_authority);
+
+        emit!(AuthorityTransferred {
+            old_authority,
+            authority: new_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless: copies the RaffleState authority to a TokenRaffle left behind by `accept_authority`.
+    pub fn sync_token_raffle_authority(ctx: Context<SyncTokenRaffleAuthority>) -> Result<()> {
+        let authority = ctx.accounts.raffle_state.authority;
+        let token_raffle = &mut ctx.accounts.token_raffle;
+        require_keys_neq!(token_raffle.authority, authority, RaffleError::InvalidAuthority);
+
+        token_raffle.authority = authority;
+
+        msg!("TokenRaffle {} authority synced to {}", token_raffle.token_mint, authority);
+
+        Ok(())
+    }
+
+    /// Only authority
+    pub fn initialize_roles(ctx: Context<InitializeRoles>) -> Result<()> {
+        let roles = &mut ctx.accounts.roles;
+        roles.members = Vec::new();
+        roles.bump = ctx.bumps.roles;
+
+        msg!("Roles initialized: {}", roles.key());
+
+        Ok(())
+    }
+
+    /// Only authority
+    /// Adds the given `ROLE_*` bits to `member`; roles already held are kept.
+    pub fn grant_role(ctx: Context<UpdateRoles>, member: Pubkey, roles: u8) -> Result<()> {
+        set_member_roles(&mut ctx.accounts.roles, member, roles, true)
+    }
+
+    /// Only authority
+    /// Removes the given `ROLE_*` bits from `member`; a member left without roles is dropped.
+    pub fn revoke_role(ctx: Context<UpdateRoles>, member: Pubkey, roles: u8) -> Result<()> {
+        set_member_roles(&mut ctx.accounts.roles, member, roles, false)
+    }
+
+    /// Only authority
+    /// Applies to rounds created afterwards; open rounds keep their price mode.
+    pub fn set_price_lock(ctx: Context<UpdateRaffleSettings>, enabled: bool) -> Result<()> {
+        let raffle_state = &mut ctx.accounts.raffle_state;
+        raffle_state.price_lock_enabled = enabled;
+
+        msg!("Ticket price lock enabled: {}", enabled);
+
+        emit!(PriceLockUpdated {
+            enabled,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    //TEST
+    /// Only authority or oracle admin: the override bypasses the pinned oracle feeds
+    pub fn set_test_ticket_price(
+        ctx: Context<SetTestTicketPrice>,
+        price: Option<u64>,
+    ) -> Result<()> {
+        let raffle_state = &mut ctx.accounts.raffle_state;
+        raffle_state.test_ticket_price = price;
+        
+        if let Some(p) = price {
+            msg!("Test ticket price set to: {} lamports", p);
+        } else {
+            msg!("Test ticket price disabled - using oracle calculation");
+        }
+        
+        Ok(())
+    }
+    //TEST
+
+    /// Only authority or operator
+    pub fn set_winner_address(
+        ctx: Context<SetWinnerAddress>,
+        round_id: u32,
+        purchase_index: u32,
+    ) -> Result<()> {
+        ctx.accounts.raffle_state.require_not_paused(PAUSE_RANDOMNESS)?;
+
+        let round = &mut ctx.accounts.round;
+        let round_tickets_purchase = &ctx.accounts.round_tickets_purchase;
+
+        require!(
+            round.status == RoundStatus::Completed,
             RaffleError::RoundNotCompleted
         );
 
         require!(
-            round.winner_address.is_none(),
-            RaffleError::WinnerAlreadySet
+            round.winner_address.is_none(),
+            RaffleError::WinnerAlreadySet
+        );
+
+        round.winner_address = Some(round_tickets_purchase.player);
+
+        msg!(
+            "✅ Winner address set for round {}: {}",
+            round_id,
+            round_tickets_purchase.player
+        );
+
+        Ok(())
+    }
+}
+
+/// Shared body of `buy_tickets_sol` and `buy_tickets_sol_with_quote`. With a
+/// quote, the purchase is rejected once it has expired (see `check_quote_expiry`) or
+/// when the recomputed cost differs from the quoted one by more than `max_deviation_bps`.
+fn process_sol_purchase(
+    ctx: Context<BuyTicketsSol>,
+    round_id: u32,
+    count: u32,
+    max_cost: u64,
+    quote: Option<(TicketQuote, u16)>,
+) -> Result<()> {
+    msg!("Tickets count: {}", count);
+    require!(count > 0, RaffleError::InvalidTicketCount);
+
+    let clock = Clock::get()?;
+    let current_time = clock.unix_timestamp;
+
+    ctx.accounts.raffle_state.require_not_paused(PAUSE_PURCHASES)?;
+
+    let round_info = ctx.accounts.round.to_account_info();
+    let round_tickets_info = ctx.accounts.round_tickets.to_account_info();
+
+    require!(
+        !ctx.accounts.sol_raffle.circuit_breaker_tripped,
+        RaffleError::CircuitBreakerTripped
+    );
+
+    let round_exists = round_info.owner == ctx.program_id && round_info.data_len() > 0;
+    let mut new_round_price = None;
+
+    if !round_exists {
+        msg!("🆕 Round {} doesn't exist, creating...", round_id);
+        ctx.accounts.raffle_state.require_not_paused(PAUSE_ROUND_CREATION)?;
+
+        // The price is guarded before the round is created, so a tripped
+        // breaker never leaves an empty round behind in the draw queue
+        let price = get_live_ticket_price_for_sol(
+            &ctx.accounts.raffle_state,
+            &ctx.accounts.sol_raffle.ticket_denomination,
+            &ctx.accounts.btc_price_feed,
+            &ctx.accounts.btc_secondary_price_feed,
+            &ctx.accounts.sol_price_feed,
+            &ctx.accounts.sol_secondary_price_feed,
+        )?;
+        if !apply_price_guard(&mut ctx.accounts.sol_raffle, price.amount, ctx.accounts.player.key(), round_id, count, current_time)? {
+            return ctx.accounts.round_tickets_purchase.close(ctx.accounts.player.to_account_info());
+        }
+        new_round_price = Some(price);
+
+        let locked_ticket_price = if ctx.accounts.raffle_state.price_lock_enabled {
+            msg!("🔒 Ticket price locked for round {}: {} lamports", round_id, price.amount);
+            Some(price.amount)
+        } else {
+            None
+        };
+
+        let rent_vault_info = ctx.accounts.rent_vault.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        create_round(
+            ctx.program_id,
+            &mut ctx.accounts.sol_raffle,
+            &ctx.accounts.raffle_state,
+            NewRoundAccounts {
+                round: &round_info,
+                round_tickets: &round_tickets_info,
+                rent_vault: &rent_vault_info,
+                system_program: &system_program_info,
+                round_bump: ctx.bumps.round,
+                round_tickets_bump: ctx.bumps.round_tickets,
+                rent_vault_bump: ctx.bumps.rent_vault,
+            },
+            round_id,
+            current_time,
+            locked_ticket_price,
+        )?;
+    }
+
+    let mut round = load_purchasable_round(
+        &ctx.accounts.sol_raffle,
+        &round_info,
+        &round_tickets_info,
+        round_id,
+        current_time,
+    )?;
+
+    let price = match (round.locked_ticket_price, new_round_price) {
+        (Some(locked_price), _) => {
+            msg!("Using locked ticket price: {} lamports", locked_price);
+            TicketPrice {
+                amount: locked_price,
+                btc_oracle_slot: None,
+                pay_oracle_slot: None,
+            }
+        }
+        // Guarded above, when this purchase created the round
+        (None, Some(price)) => price,
+        (None, None) => {
+            let price = get_live_ticket_price_for_sol(
+                &ctx.accounts.raffle_state,
+                &round.ticket_denomination,
+                &ctx.accounts.btc_price_feed,
+                &ctx.accounts.btc_secondary_price_feed,
+                &ctx.accounts.sol_price_feed,
+                &ctx.accounts.sol_secondary_price_feed,
+            )?;
+            if !apply_price_guard(&mut ctx.accounts.sol_raffle, price.amount, ctx.accounts.player.key(), round_id, count, current_time)? {
+                return ctx.accounts.round_tickets_purchase.close(ctx.accounts.player.to_account_info());
+            }
+            price
+        }
+    };
+    if let Some((quote, _)) = quote {
+        check_quote_expiry(&quote, &price, clock.slot)?;
+    }
+    let ticket_price = price.amount;
+    require!(ticket_price > 0, RaffleError::InvalidTicketPrice);
+
+    let sol_raffle = &mut ctx.accounts.sol_raffle;
+    let round_tickets_purchase = &mut ctx.accounts.round_tickets_purchase;
+    let round_tickets_purchase_bump = ctx.bumps.round_tickets_purchase;
+
+    initialize_round_tickets_purchase(
+        round_tickets_purchase,
+        round_info.key(),
+        round.purchases_count,
+        ctx.accounts.player.key(),
+        count,
+        round_tickets_purchase_bump
+    )?;
+
+    let cost = ticket_price
+        .checked_mul(count as u64)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    require!(cost <= max_cost, RaffleError::InsufficientSlippage);
+
+    if let Some((quote, max_deviation_bps)) = quote {
+        let deviation_bps = (cost.abs_diff(quote.total_cost) as u128)
+            .checked_mul(BPS_DENOMINATOR as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / quote.total_cost.max(1) as u128;
+
+        msg!("Quoted cost: {}, actual cost: {} ({} bps)", quote.total_cost, cost, deviation_bps);
+
+        require!(
+            deviation_bps <= max_deviation_bps as u128,
+            RaffleError::QuoteDeviationTooHigh
+        );
+    }
+
+    require!(
+        ctx.accounts.player.lamports() >= cost,
+        RaffleError::InsufficientFunds
+    );
+
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.player.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        cost,
+    )?;
+
+    let (prize_amount, commission_amount) = record_purchase(
+        sol_raffle.token_mint,
+        &mut round,
+        round_tickets_purchase,
+        &round_tickets_info,
+        cost,
+        current_time,
+    )?;
+    round_tickets_purchase.paid_amount = cost;
+    sol_raffle.add_obligations(cost)?;
+
+    save_round(&round_info, &round)?;
+
+    emit!(TicketPurchased {
+        token: sol_raffle.token_mint,
+        round_id: round.round_id,
+        buyer: ctx.accounts.player.key(),
+        count,
+        total_amount: cost,
+        prize_amount,
+        commission_amount,
+        denomination: round.ticket_denomination,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+pub(crate) fn validate_entrance_fee(entrance_fee_bps: u16) -> Result<()> {
+    require!(
+        entrance_fee_bps <= MAX_ENTRANCE_FEE_BPS,
+        RaffleError::InvalidEntranceFee
+    );
+    Ok(())
+}
+
+pub(crate) fn validate_beneficiaries(beneficiaries: &[BeneficiaryShare]) -> Result<()> {
+    require!(
+        !beneficiaries.is_empty() && beneficiaries.len() <= MAX_BENEFICIARIES,
+        RaffleError::InvalidBeneficiary
+    );
+
+    let mut total_bps: u64 = 0;
+    for (i, share) in beneficiaries.iter().enumerate() {
+        require_keys_neq!(share.recipient, Pubkey::default(), RaffleError::InvalidBeneficiary);
+        require!(share.share_bps > 0, RaffleError::InvalidBeneficiaryShares);
+        require!(
+            beneficiaries[..i].iter().all(|other| other.recipient != share.recipient),
+            RaffleError::InvalidBeneficiary
+        );
+        total_bps += share.share_bps as u64;
+    }
+    require!(total_bps == BPS_DENOMINATOR as u64, RaffleError::InvalidBeneficiaryShares);
+
+    Ok(())
+}
+
+/// Splits `commission` by basis-point shares, in the order of `beneficiaries`.
+/// Each share is rounded down; the rounding dust goes to the first recipient.
+pub fn split_commission(beneficiaries: &[BeneficiaryShare], commission: u64) -> Result<Vec<u64>> {
+    let mut amounts = Vec::with_capacity(beneficiaries.len());
+    let mut distributed: u64 = 0;
+    for share in beneficiaries.iter() {
+        let amount = (commission as u128 * share.share_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        distributed = distributed
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        amounts.push(amount);
+    }
+
+    if let Some(first) = amounts.first_mut() {
+        *first += commission
+            .checked_sub(distributed)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+    }
+
+    Ok(amounts)
+}
+
+pub(crate) fn set_entrance_fee(raffle_state: &mut RaffleState, entrance_fee_bps: u16) -> Result<()> {
+    validate_entrance_fee(entrance_fee_bps)?;
+
+    emit!(EntranceFeeUpdated {
+        old_entrance_fee_bps: raffle_state.entrance_fee_bps,
+        entrance_fee_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    raffle_state.entrance_fee_bps = entrance_fee_bps;
+
+    msg!("Entrance fee updated: {} bps", entrance_fee_bps);
+
+    Ok(())
+}
+
+/// Applies to rounds opened afterwards; commission of open rounds is split as snapshotted at their creation.
+pub(crate) fn set_beneficiaries(
+    raffle_state: &mut RaffleState,
+    beneficiaries: Vec<BeneficiaryShare>,
+) -> Result<()> {
+    validate_beneficiaries(&beneficiaries)?;
+
+    emit!(BeneficiariesUpdated {
+        old_beneficiaries: raffle_state.beneficiaries.clone(),
+        beneficiaries: beneficiaries.clone(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    for share in beneficiaries.iter() {
+        msg!("Beneficiary {}: {} bps", share.recipient, share.share_bps);
+    }
+    raffle_state.beneficiaries = beneficiaries;
+
+    Ok(())
+}
+
+pub(crate) fn set_price_feeds(
+    raffle_state: &mut RaffleState,
+    btc_oracle: AssetOracle,
+    sol_oracle: AssetOracle,
+) -> Result<()> {
+    btc_oracle.validate()?;
+    sol_oracle.validate()?;
+
+    emit!(PriceFeedsUpdated {
+        old_btc_oracle: raffle_state.btc_oracle,
+        old_sol_oracle: raffle_state.sol_oracle,
+        btc_oracle,
+        sol_oracle,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    raffle_state.btc_oracle = btc_oracle;
+    raffle_state.sol_oracle = sol_oracle;
+
+    msg!("Price feeds updated: BTC {:?} SOL {:?}", btc_oracle, sol_oracle);
+
+    Ok(())
+}
+
+fn set_member_roles(roles_account: &mut Roles, member: Pubkey, roles: u8, grant: bool) -> Result<()> {
+    require!(roles != 0 && roles & !ROLE_ALL == 0, RaffleError::InvalidRole);
+    require_keys_neq!(member, Pubkey::default(), RaffleError::InvalidRole);
+
+    let position = roles_account.members.iter().position(|m| m.member == member);
+    let old_roles = position.map_or(0, |i| roles_account.members[i].roles);
+    let new_roles = if grant { old_roles | roles } else { old_roles & !roles };
+
+    match position {
+        Some(i) if new_roles == 0 => {
+            roles_account.members.remove(i);
+        }
+        Some(i) => roles_account.members[i].roles = new_roles,
+        None if new_roles != 0 => {
+            require!(
+                roles_account.members.len() < MAX_ROLE_MEMBERS,
+                RaffleError::TooManyRoleMembers
+            );
+            roles_account.members.push(RoleMember { member, roles: new_roles });
+        }
+        None => {}
+    }
+
+    msg!("Roles of {}: {:#06b} -> {:#06b}", member, old_roles, new_roles);
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    if grant {
+        emit!(RoleGranted { member, roles, member_roles: new_roles, timestamp });
+    } else {
+        emit!(RoleRevoked { member, roles, member_roles: new_roles, timestamp });
+    }
+
+    Ok(())
+}
+
+fn set_paused(raffle_state: &mut RaffleState, scopes: u8, pause: bool) -> Result<()> {
+    require!(
+        scopes != 0 && scopes & !PAUSE_ALL == 0,
+        RaffleError::InvalidPauseScope
+    );
+
+    let old_paused = raffle_state.paused;
+    let paused = if pause {
+        old_paused | scopes
+    } else {
+        old_paused & !scopes
+    };
+    raffle_state.paused = paused;
+
+    msg!("⏸️ Pause scopes changed: {:#07b} -> {:#07b}", old_paused, paused);
+
+    emit!(PauseChanged {
+        old_paused,
+        paused,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+fn determine_round_to_process(sol_raffle: &Account<TokenRaffle>) -> Result<u32> {
+    if !sol_raffle.pending_rounds.is_empty() {
+        let round_id = sol_raffle.pending_rounds[0];
+        msg!("✅ Taking round {} from pending_rounds", round_id);
+        return Ok(round_id);
+    }
+    
+    let clock = Clock::get()?;
+    let current_id = sol_raffle
+        .current_round_id
+        .ok_or(RaffleError::RoundNotCreated)?;
+    
+    msg!("Found current_round_id: {}", current_id);
+    
+    let end_time = sol_raffle
+        .current_round_end_time
+        .ok_or(RaffleError::RoundNotCreated)?;
+    
+    let status = &sol_raffle.current_round_status;
+    
+    msg!("Current round end time: {}", end_time);
+    msg!("Current time: {}", clock.unix_timestamp);
+    msg!("Current round status: {:?}", status);
+    
+    require!(*status == RoundStatus::Open, RaffleError::RoundNotOpen);
+    require!(clock.unix_timestamp >= end_time, RaffleError::RoundNotEndedYet);
+    
+    Ok(current_id)
+}
+
+fn initialize_round_tickets_purchase(
+    round_tickets_purchase: &mut Account<RoundTicketsPurchase>,
+    round: Pubkey,
+    purchases_count: u32,
+    player: Pubkey,
+    tickets_count: u32,
+    round_tickets_purchase_bump: u8,
+) -> Result<()> {
+    round_tickets_purchase.round = round;
+    round_tickets_purchase.player = player;
+    round_tickets_purchase.purchase_index = purchases_count;
+    round_tickets_purchase.tickets_count = tickets_count;
+    round_tickets_purchase.paid_amount = 0;
+    round_tickets_purchase.bump = round_tickets_purchase_bump;
+    msg!(" Round tickets purchase initialized");
+    Ok(())
+}
+
+/// Accounts and bumps needed to open a new round on any `TokenRaffle`.
+pub struct NewRoundAccounts<'a, 'info> {
+    pub round: &'a AccountInfo<'info>,
+    pub round_tickets: &'a AccountInfo<'info>,
+    pub rent_vault: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub round_bump: u8,
+    pub round_tickets_bump: u8,
+    pub rent_vault_bump: u8,
+}
+
+/// Creates the `Round` and `RoundTickets` PDAs (rent paid by `rent_vault`)
+/// and makes `round_id` the current round of `token_raffle`.
+pub fn create_round<'info>(
+    program_id: &Pubkey,
+    token_raffle: &mut Account<'info, TokenRaffle>,
+    raffle_state: &RaffleState,
+    accounts: NewRoundAccounts<'_, 'info>,
+    round_id: u32,
+    current_time: i64,
+    locked_ticket_price: Option<u64>,
+) -> Result<()> {
+    let rent = Rent::get()?;
+    let token_raffle_key = token_raffle.key();
+    let round_end_time = get_temporary_close_time(current_time);
+
+    let vault_seeds = &[b"rent_vault".as_ref(), &[accounts.rent_vault_bump]];
+
+    // ========== 1. Create Round account ==========
+    let round_seeds = &[
+        b"round".as_ref(),
+        token_raffle_key.as_ref(),
+        &round_id.to_le_bytes(),
+        &[accounts.round_bump],
+    ];
+    let round_space = 8 + Round::INIT_SPACE;
+    let round_lamports = rent.minimum_balance(round_space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            accounts.rent_vault.key,
+            accounts.round.key,
+            round_lamports,
+            round_space as u64,
+            program_id,
+        ),
+        &[
+            accounts.rent_vault.clone(),
+            accounts.round.clone(),
+            accounts.system_program.clone(),
+        ],
+        &[vault_seeds, round_seeds],
+    )?;
+
+    // Initialize Round
+    {
+        let mut round_account_data = accounts.round.try_borrow_mut_data()?;
+
+        let round_data = Round {
+            initialized: true,
+            token_raffle: token_raffle_key,
+            round_id,
+            status: RoundStatus::Open,
+            start_time: current_time,
+            end_time: round_end_time,
+            prize_amount: 0,
+            commission_balance: 0,
+            total_received: 0,
+            commission_remainder: 0,
+            purchases_count: 0,
+            total_tickets: 0,
+            winner_ticket_index: None,
+            winner_purchase_index: None,
+            winner_address: None,
+            prize_claimed: false,
+            entrance_fee_bps: raffle_state.entrance_fee_bps,
+            beneficiaries: raffle_state.beneficiaries.clone(),
+            ticket_denomination: token_raffle.ticket_denomination,
+            locked_ticket_price,
+            bump: accounts.round_bump,
+        };
+
+        let mut writer = &mut round_account_data[..];
+        round_data.try_serialize(&mut writer)?;
+    }
+
+    // ========== 2. Create RoundTickets account ==========
+    let round_key = accounts.round.key();
+    let round_tickets_seeds = &[
+        b"round_tickets".as_ref(),
+        round_key.as_ref(),
+        &[accounts.round_tickets_bump],
+    ];
+    let tickets_space = 8 + std::mem::size_of::<RoundTickets>();
+    let tickets_lamports = rent.minimum_balance(tickets_space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            accounts.rent_vault.key,
+            accounts.round_tickets.key,
+            tickets_lamports,
+            tickets_space as u64,
+            program_id,
+        ),
+        &[
+            accounts.rent_vault.clone(),
+            accounts.round_tickets.clone(),
+            accounts.system_program.clone(),
+        ],
+        &[vault_seeds, round_tickets_seeds],
+    )?;
+
+    // Initialize RoundTickets
+    {
+        let mut tickets_data = accounts.round_tickets.try_borrow_mut_data()?;
+        let discriminator = RoundTickets::DISCRIMINATOR;
+        tickets_data[0..8].copy_from_slice(&discriminator);
+
+        for byte in &mut tickets_data[8..] {
+            *byte = 0;
+        }
+
+        tickets_data[8..40].copy_from_slice(round_key.as_ref());
+
+        let bump_offset = 8 + 32 + (1024 * 4) + (1024 * 4) + 4;
+        tickets_data[bump_offset] = accounts.round_tickets_bump;
+    }
+
+    msg!(
+        "✅ Round {} and RoundTickets created (paid {} lamports total)",
+        round_id,
+        round_lamports + tickets_lamports
+    );
+
+    if let Some(prev_id) = token_raffle.current_round_id {
+        let prev_round_end = token_raffle.current_round_end_time.unwrap();
+
+        if current_time >= prev_round_end {
+            if token_raffle.current_round_status == RoundStatus::Open {
+                if !token_raffle.pending_rounds.contains(&prev_id) {
+                    token_raffle.pending_rounds.push(prev_id);
+                    msg!("✅ Added round {} to pending (status: Open)", prev_id);
+                }
+            } else {
+                msg!("⚠️ Round {} already Completed, not adding to pending", prev_id);
+            }
+        }
+    }
+
+    token_raffle.current_round_id = Some(round_id);
+    token_raffle.total_rounds = token_raffle
+        .total_rounds
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    token_raffle.current_round_status = RoundStatus::Open;
+    token_raffle.current_round_end_time = Some(round_end_time);
+
+    Ok(())
+}
+
+/// Deserializes `round` and checks that it is the round currently accepting
+/// purchases for `token_raffle` and that `round_tickets` belongs to it.
+pub fn load_purchasable_round(
+    token_raffle: &TokenRaffle,
+    round_info: &AccountInfo,
+    round_tickets_info: &AccountInfo,
+    round_id: u32,
+    current_time: i64,
+) -> Result<Round> {
+    let round_data = round_info.try_borrow_data()?;
+    let mut round_reader = &round_data[..];
+    let round = Round::try_deserialize(&mut round_reader)?;
+    drop(round_data); // Release the borrow immediately
+
+    let current_round_id = token_raffle.current_round_id.unwrap_or(0);
+    let current_round_end_time = token_raffle.current_round_end_time.unwrap_or(i64::MAX);
+
+    let is_current_round = round_id == current_round_id && current_time < current_round_end_time;
+    let is_next_round = round_id == current_round_id + 1 && current_time >= current_round_end_time;
+
+    require!(
+        is_current_round || is_next_round,
+        RaffleError::RoundNotAvailable
+    );
+
+    require!(round.initialized, RaffleError::RoundNotInitialized);
+    require!(round.status == RoundStatus::Open, RaffleError::RoundNotOpen);
+
+    // Verify round_tickets connection
+    {
+        let tickets_data = round_tickets_info.try_borrow_data()?;
+        let tickets_round_key = Pubkey::try_from(&tickets_data[8..40])
+            .map_err(|_| RaffleError::RoundTicketsNotInitialized)?;
+        require!(
+            tickets_round_key == round_info.key(),
+            RaffleError::RoundTicketsNotInitialized
         );
+    }
 
-        round.winner_address = Some(round_tickets_purchase.player);
+    Ok(round)
+}
 
-        msg!(
-            "✅ Winner address set for round {}: {}",
-            round_id,
-            round_tickets_purchase.player
-        );
+/// Checks a freshly computed ticket price against the raffle's `PriceGuard`.
+/// Purchases are rejected until the authority has configured the guard, and
+/// prices outside the bounds are rejected. A move of more than
+/// `max_price_move_bps` from the last accepted price within `price_move_window`
+/// trips the circuit breaker and returns `false`; the caller must then abort the
+/// purchase without failing, so the tripped state is persisted. The transaction
+/// succeeds without tickets, and `CircuitBreakerTripped` names the aborted purchase.
+pub fn apply_price_guard(
+    token_raffle: &mut TokenRaffle,
+    ticket_price: u64,
+    buyer: Pubkey,
+    round_id: u32,
+    tickets_requested: u32,
+    current_time: i64,
+) -> Result<bool> {
+    let guard = token_raffle.price_guard;
 
-        Ok(())
+    require!(guard.is_configured(), RaffleError::PriceGuardNotConfigured);
+    require!(
+        ticket_price >= guard.min_ticket_price && ticket_price <= guard.max_ticket_price,
+        RaffleError::TicketPriceOutOfBounds
+    );
+
+    if let Some(last_price) = token_raffle.last_ticket_price {
+        let within_window =
+            current_time.saturating_sub(token_raffle.last_ticket_price_at) <= guard.price_move_window;
+
+        if guard.max_price_move_bps > 0 && within_window {
+            let move_bps = (ticket_price.abs_diff(last_price) as u128)
+                .checked_mul(BPS_DENOMINATOR as u128)
+                .ok_or(ProgramError::ArithmeticOverflow)?
+                / last_price as u128;
+
+            if move_bps > guard.max_price_move_bps as u128 {
+                token_raffle.circuit_breaker_tripped = true;
+
+                msg!(
+                    "⛔ Circuit breaker tripped: price moved {} bps ({} -> {})",
+                    move_bps,
+                    last_price,
+                    ticket_price
+                );
+
+                emit!(CircuitBreakerTripped {
+                    token: token_raffle.token_mint,
+                    last_ticket_price: last_price,
+                    ticket_price,
+                    move_bps: move_bps as u64,
+                    buyer,
+                    round_id,
+                    tickets_requested,
+                    timestamp: current_time,
+                });
+
+                return Ok(false);
+            }
+        }
     }
+
+    token_raffle.last_ticket_price = Some(ticket_price);
+    token_raffle.last_ticket_price_at = current_time;
+
+    Ok(true)
 }
 
-fn determine_round_to_process(sol_raffle: &Account<TokenRaffle>) -> Result<u32> {
-    if !sol_raffle.pending_rounds.is_empty() {
-        let round_id = sol_raffle.pending_rounds[0];
-        msg!("✅ Taking round {} from pending_rounds", round_id);
-        return Ok(round_id);
+/// Splits `cost` into prize and commission, appends the purchase to
+/// `RoundTickets` and awards the first-buyer bonus ticket.
+/// Returns `(prize_amount, commission_amount)`.
+pub fn record_purchase(
+    token_mint: Pubkey,
+    round: &mut Round,
+    round_tickets_purchase: &mut Account<RoundTicketsPurchase>,
+    round_tickets_info: &AccountInfo,
+    cost: u64,
+    current_time: i64,
+) -> Result<(u64, u64)> {
+    let (prize_amount, commission_amount) = split_purchase(round, cost)?;
+
+    let is_first_buyer = round.total_tickets == 0;
+
+    let mut new_total = round
+        .total_tickets
+        .checked_add(round_tickets_purchase.tickets_count)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    if is_first_buyer {
+        new_total = new_total + 1;
+
+        round_tickets_purchase.tickets_count = round_tickets_purchase
+            .tickets_count
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        emit!(FirstTicketBonusAwarded {
+            token: token_mint,
+            round_id: round.round_id,
+            buyer: round_tickets_purchase.player,
+            timestamp: current_time,
+            round_start_time: round.start_time,
+            round_end_time: round.end_time,
+        });
     }
-    
-    let clock = Clock::get()?;
-    let current_id = sol_raffle
-        .current_round_id
-        .ok_or(RaffleError::RoundNotCreated)?;
-    
-    msg!("Found current_round_id: {}", current_id);
-    
-    let end_time = sol_raffle
-        .current_round_end_time
-        .ok_or(RaffleError::RoundNotCreated)?;
-    
-    let status = &sol_raffle.current_round_status;
-    
-    msg!("Current round end time: {}", end_time);
-    msg!("Current time: {}", clock.unix_timestamp);
-    msg!("Current round status: {:?}", status);
-    
-    require!(*status == RoundStatus::Open, RaffleError::RoundNotOpen);
-    require!(clock.unix_timestamp >= end_time, RaffleError::RoundNotEndedYet);
-    
-    Ok(current_id)
+
+    // Update round_tickets using zero-copy mutation
+    {
+        let mut tickets_data = round_tickets_info.try_borrow_mut_data()?;
+
+        // Read current len (offset: 8 + 32 + 4096 + 4096 = 8232)
+        let len_offset = 8 + 32 + (1024 * 4) + (1024 * 4);
+        let len = u32::from_le_bytes([
+            tickets_data[len_offset],
+            tickets_data[len_offset + 1],
+            tickets_data[len_offset + 2],
+            tickets_data[len_offset + 3],
+        ]) as usize;
+
+        // Write new cumulative value
+        let cumulative_bytes = new_total.to_le_bytes();
+        if len < 1024 {
+            // Write to cumulative_tickets_1 (offset: 8 + 32)
+            let ticket_offset = 8 + 32 + (len * 4);
+            tickets_data[ticket_offset..ticket_offset + 4].copy_from_slice(&cumulative_bytes);
+        } else {
+            // Write to cumulative_tickets_2 (offset: 8 + 32 + 4096)
+            let ticket_offset = 8 + 32 + (1024 * 4) + ((len - 1024) * 4);
+            tickets_data[ticket_offset..ticket_offset + 4].copy_from_slice(&cumulative_bytes);
+        }
+
+        // Update len
+        let new_len = (len + 1) as u32;
+        tickets_data[len_offset..len_offset + 4].copy_from_slice(&new_len.to_le_bytes());
+    }
+
+    round.total_tickets = new_total;
+    round.purchases_count += 1;
+
+    Ok((prize_amount, commission_amount))
 }
 
-fn initialize_round_tickets_purchase(
-    round_tickets_purchase: &mut Account<RoundTicketsPurchase>,
-    round: Pubkey,
-    purchases_count: u32,
-    player: Pubkey,
-    tickets_count: u32,
-    round_tickets_purchase_bump: u8,
-) -> Result<()> {
-    round_tickets_purchase.round = round;
-    round_tickets_purchase.player = player;
-    round_tickets_purchase.purchase_index = purchases_count;
-    round_tickets_purchase.tickets_count = tickets_count;
-    round_tickets_purchase.bump = round_tickets_purchase_bump;
-    msg!(" Round tickets purchase initialized");
+/// Adds `cost` to the round's prize and commission by its `entrance_fee_bps`.
+/// Returns `(prize_amount, commission_amount)`.
+///
+/// Rounding rule: commission is rounded down per round, not per purchase.
+/// Each purchase adds `cost * entrance_fee_bps` to the round's running
+/// remainder, whole units go to commission and the rest (< 1 unit, kept in
+/// `commission_remainder`) carries over to the next purchase. A round's
+/// commission is therefore `floor(total_received * entrance_fee_bps / 10_000)`
+/// and `prize_amount + commission_balance == total_received` always holds.
+fn split_purchase(round: &mut Round, cost: u64) -> Result<(u64, u64)> {
+    let fee_numerator = (cost as u128)
+        .checked_mul(round.entrance_fee_bps as u128)
+        .ok_or(ProgramError::ArithmeticOverflow)?
+        .checked_add(round.commission_remainder as u128)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    let commission_amount = u64::try_from(fee_numerator / BPS_DENOMINATOR as u128)
+        .map_err(|_| ProgramError::ArithmeticOverflow)?;
+    round.commission_remainder = (fee_numerator % BPS_DENOMINATOR as u128) as u16;
+
+    round.commission_balance = round
+        .commission_balance
+        .checked_add(commission_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    let prize_amount = cost
+        .checked_sub(commission_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    round.prize_amount = round
+        .prize_amount
+        .checked_add(prize_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    round.total_received = round
+        .total_received
+        .checked_add(cost)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    require!(
+        round.prize_amount.checked_add(round.commission_balance) == Some(round.total_received),
+        RaffleError::AccountingMismatch
+    );
+
+    Ok((prize_amount, commission_amount))
+}
+
+pub fn save_round(round_info: &AccountInfo, round: &Round) -> Result<()> {
+    let mut round_data = round_info.try_borrow_mut_data()?;
+    let mut writer = &mut round_data[..];
+    round.try_serialize(&mut writer)?;
     Ok(())
 }
 
@@ -1047,6 +1875,7 @@ pub fn change_round_status(
     let old_status = round.status.clone();
 
     require!(old_status != RoundStatus::Completed, RaffleError::CannotChangeCompletedRound);
+    require!(old_status != RoundStatus::Cancelled, RaffleError::RoundCancelled);
 
     round.status = new_status.clone();
 
@@ -1070,54 +1899,185 @@ pub fn change_round_status(
     Ok(())
 }
 
+/// A quote expires `QUOTE_VALIDITY_SLOTS` after the oracle updates it was priced from.
+/// Its oracle slots are caller-supplied, so for every feed the purchase read the quote
+/// must carry a slot no later than the one just read. Locked and test prices read no
+/// feed and are covered by the deviation check alone.
+fn check_quote_expiry(quote: &TicketQuote, ticket_price: &TicketPrice, slot: u64) -> Result<()> {
+    for (quoted_slot, read_slot) in [
+        (quote.btc_oracle_slot, ticket_price.btc_oracle_slot),
+        (quote.sol_oracle_slot, ticket_price.pay_oracle_slot),
+    ] {
+        let Some(read_slot) = read_slot else {
+            continue;
+        };
+        let quoted_slot = quoted_slot.ok_or(RaffleError::QuoteExpired)?;
+        require!(quoted_slot <= read_slot, RaffleError::QuoteExpired);
+        require!(
+            slot <= quoted_slot.saturating_add(QUOTE_VALIDITY_SLOTS),
+            RaffleError::QuoteExpired
+        );
+    }
+    Ok(())
+}
+
+/// Ticket price in base units of the pay asset and the slots of the oracle
+/// updates it was derived from (`None` when a feed was not needed).
+#[derive(Clone, Copy, Debug)]
+pub struct TicketPrice {
+    pub amount: u64,
+    pub btc_oracle_slot: Option<u64>,
+    pub pay_oracle_slot: Option<u64>,
+}
+
+/// Ticket price a SOL purchase pays right now: the test override if set,
+/// otherwise the oracle price.
+pub fn get_live_ticket_price_for_sol(
+    raffle_state: &RaffleState,
+    denomination: &TicketDenomination,
+    btc_price_feed: &AccountInfo,
+    btc_secondary_price_feed: &AccountInfo,
+    sol_price_feed: &AccountInfo,
+    sol_secondary_price_feed: &AccountInfo,
+) -> Result<TicketPrice> {
+    if let Some(test_price) = raffle_state.test_ticket_price {
+        msg!("Using test ticket price: {} lamports", test_price);
+        return Ok(TicketPrice {
+            amount: test_price,
+            btc_oracle_slot: None,
+            pay_oracle_slot: None,
+        });
+    }
+
+    msg!("Calculating ticket price from oracle feeds");
+    calculate_ticket_price_for_sol(
+        raffle_state,
+        denomination,
+        btc_price_feed,
+        btc_secondary_price_feed,
+        sol_price_feed,
+        sol_secondary_price_feed,
+    )
+}
+
 fn calculate_ticket_price_for_sol(
+    raffle_state: &RaffleState,
+    denomination: &TicketDenomination,
     btc_price_feed: &AccountInfo,
+    btc_secondary_price_feed: &AccountInfo,
     sol_price_feed: &AccountInfo,
-) -> Result<u64> {
+    sol_secondary_price_feed: &AccountInfo,
+) -> Result<TicketPrice> {
     msg!("--- calculate_ticket_price_for_sol START ---");
 
-    let clock = Clock::get()?;
-    msg!("Current slot: {}", clock.slot);
+    let ticket_price = calculate_ticket_price(
+        raffle_state,
+        denomination,
+        btc_price_feed,
+        btc_secondary_price_feed,
+        &raffle_state.sol_oracle,
+        sol_price_feed,
+        sol_secondary_price_feed,
+        "SOL",
+        SOL_DECIMALS,
+    )?;
 
-    // ===== SOL feed =====
-    msg!("Parsing SOL price feed...");
-    let sol_data = sol_price_feed.data.borrow();
+    msg!("Final ticket price (lamports): {}", ticket_price.amount);
+    msg!("--- calculate_ticket_price_for_sol END ---");
+
+    Ok(ticket_price)
+}
 
-    let sol_feed = PullFeedAccountData::parse(sol_data).map_err(|e| {
-        msg!("SOL Switchboard parse failed: {:?}", e);
-        RaffleError::InvalidFeedAccount
-    })?;
+/// Ticket price in the smallest units of an SPL token with `token_decimals`
+/// decimals, priced through the token's USD feed.
+pub fn calculate_ticket_price_for_token(
+    raffle_state: &RaffleState,
+    denomination: &TicketDenomination,
+    btc_price_feed: &AccountInfo,
+    btc_secondary_price_feed: &AccountInfo,
+    supported_token: &SupportedToken,
+    token_price_feed: &AccountInfo,
+    token_secondary_price_feed: &AccountInfo,
+) -> Result<TicketPrice> {
+    msg!("--- calculate_ticket_price_for_token START ---");
+
+    let ticket_price = calculate_ticket_price(
+        raffle_state,
+        denomination,
+        btc_price_feed,
+        btc_secondary_price_feed,
+        &supported_token.oracle,
+        token_price_feed,
+        token_secondary_price_feed,
+        "Token",
+        supported_token.decimals,
+    )?;
 
-    let sol_price = sol_feed
-        .get_value(clock.slot, 1500, 3, false)
-        .map_err(|e| {
-            msg!("SOL Switchboard get_value failed: {:?}", e);
-            RaffleError::OracleError
-        })?;
+    msg!("Final ticket price (token base units): {}", ticket_price.amount);
+    msg!("--- calculate_ticket_price_for_token END ---");
 
-    msg!("SOL Price (Decimal): {}", sol_price);
+    Ok(ticket_price)
+}
 
-    // ===== BTC feed =====
-    msg!("Parsing BTC price feed...");
-    let btc_data = btc_price_feed.data.borrow();
+/// Converts `denomination` into base units of the pay asset. Oracle feeds are
+/// only read when the denomination needs them.
+#[allow(clippy::too_many_arguments)]
+fn calculate_ticket_price(
+    raffle_state: &RaffleState,
+    denomination: &TicketDenomination,
+    btc_price_feed: &AccountInfo,
+    btc_secondary_price_feed: &AccountInfo,
+    pay_oracle: &AssetOracle,
+    pay_price_feed: &AccountInfo,
+    pay_secondary_price_feed: &AccountInfo,
+    pay_label: &str,
+    pay_decimals: u8,
+) -> Result<TicketPrice> {
+    let mut btc_oracle_slot = None;
+
+    let ticket_price_usd = match denomination.asset {
+        TicketAsset::Native => {
+            msg!("Ticket price in {} base units: {}", pay_label, denomination.amount);
+            return Ok(TicketPrice {
+                amount: denomination.amount,
+                btc_oracle_slot: None,
+                pay_oracle_slot: None,
+            });
+        }
+        TicketAsset::Usd => {
+            Decimal::from_i128_with_scale(denomination.amount as i128, USD_DECIMALS as u32)
+        }
+        TicketAsset::Btc => {
+            let btc_price = price_feeds::get_asset_price(
+                &raffle_state.btc_oracle,
+                btc_price_feed,
+                btc_secondary_price_feed,
+                "BTC",
+            )?;
+            btc_oracle_slot = Some(btc_price.slot);
+            get_ticket_price_in_usd(denomination.amount, btc_price.price)?
+        }
+    };
 
-    let btc_feed = PullFeedAccountData::parse(btc_data).map_err(|e| {
-        msg!("BTC Switchboard parse failed: {:?}", e);
-        RaffleError::InvalidFeedAccount
-    })?;
+    msg!("Ticket price in USD: {}", ticket_price_usd);
 
-    let btc_price = btc_feed
-        .get_value(clock.slot, 1500, 1, false)
-        .map_err(|e| {
-            msg!("BTC Switchboard get_value failed: {:?}", e);
-            RaffleError::OracleError
-        })?;
+    let pay_price = price_feeds::get_asset_price(
+        pay_oracle,
+        pay_price_feed,
+        pay_secondary_price_feed,
+        pay_label,
+    )?;
 
-    msg!("BTC Price (Decimal): {}", btc_price);
+    Ok(TicketPrice {
+        amount: convert_usd_to_base_units(ticket_price_usd, pay_price.price, pay_decimals)?,
+        btc_oracle_slot,
+        pay_oracle_slot: Some(pay_price.slot),
+    })
+}
 
-    // ===== Ticket price calculation =====
+fn get_ticket_price_in_usd(ticket_price_satoshis: u64, btc_price: Decimal) -> Result<Decimal> {
     let ticket_price_btc =
-        Decimal::from_str(TICKET_BTC_STR).map_err(|_| ProgramError::ArithmeticOverflow)?;
+        Decimal::from_i128_with_scale(ticket_price_satoshis as i128, BTC_DECIMALS as u32);
 
     msg!("Ticket price in BTC: {}", ticket_price_btc);
 
@@ -1125,34 +2085,33 @@ fn calculate_ticket_price_for_sol(
         .checked_mul(ticket_price_btc)
         .ok_or(ProgramError::ArithmeticOverflow)?;
 
-    msg!("Ticket price in USD: {}", ticket_price_usd);
+    Ok(ticket_price_usd)
+}
 
-    let ticket_price_sol = ticket_price_usd
-        .checked_div(sol_price)
+/// Converts a USD amount into base units of an asset priced at `asset_price` USD
+/// with `decimals` decimals, rounding to the nearest unit.
+fn convert_usd_to_base_units(usd_amount: Decimal, asset_price: Decimal, decimals: u8) -> Result<u64> {
+    let asset_amount = usd_amount
+        .checked_div(asset_price)
         .ok_or(ProgramError::ArithmeticOverflow)?;
 
-    msg!("Ticket price in SOL: {}", ticket_price_sol);
+    msg!("Amount in asset: {}", asset_amount);
 
-    let lamports_decimal = ticket_price_sol
-        .checked_mul(Decimal::from(LAMPORTS_PER_SOL))
+    let base_units_decimal = asset_amount
+        .checked_mul(Decimal::from(10u64.pow(decimals as u32)))
         .ok_or(ProgramError::ArithmeticOverflow)?;
 
-    msg!("Lamports (Decimal): {}", lamports_decimal);
-
-    let lamports_u128 = lamports_decimal
+    let base_units_u128 = base_units_decimal
         .round()
         .to_u128()
         .ok_or(ProgramError::ArithmeticOverflow)?;
 
-    msg!("Lamports (rounded, u128): {}", lamports_u128);
+    msg!("Base units (rounded, u128): {}", base_units_u128);
 
-    let lamports = u64::try_from(lamports_u128)
+    let base_units = u64::try_from(base_units_u128)
         .map_err(|_| ProgramError::ArithmeticOverflow)?;
 
-    msg!("Final ticket price (lamports): {}", lamports);
-    msg!("--- calculate_ticket_price_for_sol END ---");
-
-    Ok(lamports)
+    Ok(base_units)
 }
 
 fn transfer<'a>(
@@ -1235,38 +2194,28 @@ pub struct FundRentVault<'info> {
     pub system_program: Program<'info, System>,
 }
 
+//TEST
 #[derive(Accounts)]
-pub struct WithdrawRentVault<'info> {
+#[instruction(round_id: u32)]
+pub struct InitializeRound<'info> {
     #[account(
-        mut,
         seeds = [b"raffle_state"],
         bump = raffle_state.bump,
-        has_one = authority @ RaffleError::Unauthorized
+        constraint = raffle_state.has_role(roles.as_deref(), &authority.key(), ROLE_OPERATOR) @ RaffleError::Unauthorized
     )]
     pub raffle_state: Account<'info, RaffleState>,
-    
+
+    /// Optional: without it only the authority passes the role check
     #[account(
-        mut,
-        seeds = [b"rent_vault"],
-        bump
+        seeds = [b"roles"],
+        bump = roles.bump
     )]
-    pub rent_vault: SystemAccount<'info>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
+    pub roles: Option<Account<'info, Roles>>,
 
-//TEST
-#[derive(Accounts)]
-#[instruction(round_id: u32)]
-pub struct InitializeRound<'info> {
     #[account(
         mut,
         seeds = [b"sol_raffle"],
-        bump = sol_raffle.bump,
-        constraint = sol_raffle.authority == authority.key() @ RaffleError::Unauthorized
+        bump = sol_raffle.bump
     )]
     pub sol_raffle: Account<'info, TokenRaffle>,
     
@@ -1352,35 +2301,60 @@ pub struct FundVrfVault<'info> {
     /// PDA for storing funds for VRF
     #[account(
         mut,
-        seeds = [b"vrf_fee_vault"],
+        seeds = [b"vrf_fee_vault"],
+        bump
+    )]
+    pub vrf_fee_vault: SystemAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct WithdrawCommissionSol<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_raffle"],
+        bump = sol_raffle.bump
+    )]
+    pub sol_raffle: Account<'info, TokenRaffle>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
         bump
     )]
-    pub vrf_fee_vault: SystemAccount<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
+    /// CHECK: vault for storing SOL
+    pub vault: AccountInfo<'info>,
+
+    /// CHECK: commission recipient, matched against the proposal
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
 
-#[derive(Accounts)]
-pub struct WithdrawVrfVault<'info> {
     #[account(
-        mut,
-        seeds = [b"raffle_state"],
-        bump = raffle_state.bump,
-        has_one = authority @ RaffleError::Unauthorized
+        seeds = [b"multisig"],
+        bump = multisig.bump
     )]
-    pub raffle_state: Account<'info, RaffleState>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
+    pub multisig: Account<'info, Multisig>,
+
     #[account(
         mut,
-        seeds = [b"vrf_fee_vault"],
-        bump
+        close = proposer,
+        seeds = [b"proposal", &proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer @ RaffleError::Unauthorized
     )]
-    pub vrf_fee_vault: SystemAccount<'info>,
-    
-    pub system_program: Program<'info, System>,
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: receives the proposal rent, matched by `has_one`
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
@@ -1453,12 +2427,22 @@ pub struct BuyTicketsSol<'info> {
     /// CHECK: vault for storing SOL
     pub vault: AccountInfo<'info>,
 
-    /// CHECK: BTC price feed account
+    /// CHECK: BTC primary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.btc_oracle.primary.address @ RaffleError::InvalidFeedAccount)]
     pub btc_price_feed: AccountInfo<'info>,
 
-    /// CHECK: SOL price feed account
+    /// CHECK: BTC secondary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.btc_oracle.secondary.address @ RaffleError::InvalidFeedAccount)]
+    pub btc_secondary_price_feed: AccountInfo<'info>,
+
+    /// CHECK: SOL primary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.sol_oracle.primary.address @ RaffleError::InvalidFeedAccount)]
     pub sol_price_feed: AccountInfo<'info>,
 
+    /// CHECK: SOL secondary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.sol_oracle.secondary.address @ RaffleError::InvalidFeedAccount)]
+    pub sol_secondary_price_feed: AccountInfo<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -1518,21 +2502,175 @@ pub struct ClaimPrizeSol<'info> {
         bump
     )]
     pub rent_vault: SystemAccount<'info>,
+}
 
+#[derive(Accounts)]
+pub struct TestCalculatePrice<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    /// CHECK: BTC primary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.btc_oracle.primary.address @ RaffleError::InvalidFeedAccount)]
+    pub btc_price_feed: AccountInfo<'info>,
+
+    /// CHECK: BTC secondary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.btc_oracle.secondary.address @ RaffleError::InvalidFeedAccount)]
+    pub btc_secondary_price_feed: AccountInfo<'info>,
+
+    /// CHECK: SOL primary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.sol_oracle.primary.address @ RaffleError::InvalidFeedAccount)]
+    pub sol_price_feed: AccountInfo<'info>,
+
+    /// CHECK: SOL secondary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.sol_oracle.secondary.address @ RaffleError::InvalidFeedAccount)]
+    pub sol_secondary_price_feed: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTokenRaffleSettings<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        constraint = raffle_state.has_role(roles.as_deref(), &authority.key(), ROLE_ORACLE_ADMIN) @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    /// Optional: without it only the authority passes the role check
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    pub authority: Signer<'info>,
+
+    /// SOL raffle or any per-mint token raffle
     #[account(
         mut,
-        constraint = beneficiary.key() == raffle_state.beneficiary @ RaffleError::InvalidBeneficiary
+        constraint = token_raffle.key() == token_raffle.address()? @ RaffleError::InvalidTokenRaffle
     )]
-    /// CHECK: key validated via constraint
-    pub beneficiary: AccountInfo<'info>,
+    pub token_raffle: Account<'info, TokenRaffle>,
 }
 
 #[derive(Accounts)]
-pub struct TestCalculatePrice<'info> {
-    /// CHECK: Switchboard BTC price feed
-    pub btc_price_feed: AccountInfo<'info>,
-    /// CHECK: Switchboard SOL price feed
-    pub sol_price_feed: AccountInfo<'info>,
+pub struct UpdateRaffleSettings<'info> {
+    #[account(
+        mut,
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        has_one = authority @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        constraint = raffle_state.has_role(roles.as_deref(), &authority.key(), ROLE_PAUSER) @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    /// Optional: without it only the authority passes the role check
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRoles<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        has_one = authority @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Roles::INIT_SPACE,
+        seeds = [b"roles"],
+        bump
+    )]
+    pub roles: Account<'info, Roles>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRoles<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        has_one = authority @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        mut,
+        seeds = [b"roles"],
+        bump = roles.bump
+    )]
+    pub roles: Account<'info, Roles>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        constraint = raffle_state.pending_authority == Some(new_authority.key()) @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        mut,
+        seeds = [CLIENT_STATE_SEED],
+        bump = client_state.bump
+    )]
+    pub client_state: Account<'info, ClientState>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_raffle"],
+        bump = sol_raffle.bump
+    )]
+    pub sol_raffle: Account<'info, TokenRaffle>,
+
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SyncTokenRaffleAuthority<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        mut,
+        constraint = token_raffle.key() == token_raffle.address()? @ RaffleError::InvalidTokenRaffle
+    )]
+    pub token_raffle: Account<'info, TokenRaffle>,
 }
 
 #[derive(Accounts)]
@@ -1545,10 +2683,10 @@ pub struct RequestRandomness<'info> {
     )]
     pub raffle_state: Account<'info, RaffleState>,
 
+    /// SOL raffle or any per-mint token raffle
     #[account(
         mut,
-        seeds = [b"sol_raffle"],
-        bump = sol_raffle.bump
+        constraint = sol_raffle.key() == sol_raffle.address()? @ RaffleError::InvalidTokenRaffle
     )]
     pub sol_raffle: Account<'info, TokenRaffle>,
 
@@ -1600,9 +2738,17 @@ pub struct SetTestTicketPrice<'info> {
     #[account(
         mut,
         seeds = [b"raffle_state"],
-        bump = raffle_state.bump
+        bump = raffle_state.bump,
+        constraint = raffle_state.has_role(roles.as_deref(), &authority.key(), ROLE_ORACLE_ADMIN) @ RaffleError::Unauthorized
     )]
     pub raffle_state: Account<'info, RaffleState>,
+
+    /// Optional: without it only the authority passes the role check
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump
+    )]
+    pub roles: Option<Account<'info, Roles>>,
     
     pub authority: Signer<'info>,
 }
@@ -1615,14 +2761,21 @@ pub struct SetWinnerAddress<'info> {
         mut,
         seeds = [b"raffle_state"],
         bump = raffle_state.bump,
-        has_one = authority @ RaffleError::Unauthorized
+        constraint = raffle_state.has_role(roles.as_deref(), &authority.key(), ROLE_OPERATOR) @ RaffleError::Unauthorized
     )]
     pub raffle_state: Account<'info, RaffleState>,
 
+    /// Optional: without it only the authority passes the role check
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    /// SOL raffle or any per-mint token raffle
     #[account(
         mut,
-        seeds = [b"sol_raffle"],
-        bump = sol_raffle.bump
+        constraint = sol_raffle.key() == sol_raffle.address()? @ RaffleError::InvalidTokenRaffle
     )]
     pub sol_raffle: Account<'info, TokenRaffle>,
 
@@ -1656,12 +2809,67 @@ pub struct SetWinnerAddress<'info> {
 #[derive(InitSpace)]
 pub struct RaffleState {
     pub authority: Pubkey,
-    pub entrance_fee_percentage: u8,
-    pub beneficiary: Pubkey,
+    pub entrance_fee_bps: u16,
+    #[max_len(MAX_BENEFICIARIES)]
+    pub beneficiaries: Vec<BeneficiaryShare>, // commission split, shares sum to 10_000 bps
     pub created_at: i64,
     pub vrf_request_counter: u8,
     pub bump: u8,
     pub test_ticket_price: Option<u64>,
+    pub btc_oracle: AssetOracle, // pinned BTC/USD feeds
+    pub sol_oracle: AssetOracle, // pinned SOL/USD feeds
+    pub price_lock_enabled: bool, // freeze the ticket price for the lifetime of each round
+    pub paused: u8, // bitmask of PAUSE_* scopes
+    pub pending_authority: Option<Pubkey>, // proposed by the authority, set once accepted
+    pub timelock_delay: i64, // seconds between queueing and executing a timelocked change
+    pub rent_reserve_rounds: u32, // future rounds whose rent must stay in rent_vault
+    pub vrf_reserve_requests: u32, // VRF request fees that must stay in vrf_fee_vault
+    pub vault_top_up_bps: u16, // slice of each SOL round's commission routed into the vaults
+    pub rent_vault_target: u64, // top-ups stop once rent_vault holds this many lamports
+    pub vrf_vault_target: u64, // top-ups stop once vrf_fee_vault holds this many lamports
+}
+
+impl RaffleState {
+    /// The authority is the super-admin and passes every role check.
+    pub fn has_role(&self, roles: Option<&Roles>, key: &Pubkey, role: u8) -> bool {
+        *key == self.authority || roles.is_some_and(|roles| roles.has_role(key, role))
+    }
+
+    pub fn require_not_paused(&self, scope: u8) -> Result<()> {
+        require!(self.paused & scope == 0, RaffleError::Paused);
+        Ok(())
+    }
+
+    /// Lamports `rent_vault` must keep to open `rent_reserve_rounds` more rounds.
+    pub fn rent_reserve_floor(&self, rent: &Rent) -> Result<u64> {
+        let per_round = rent
+            .minimum_balance(8 + Round::INIT_SPACE)
+            .checked_add(rent.minimum_balance(8 + std::mem::size_of::<RoundTickets>()))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(per_round
+            .checked_mul(self.rent_reserve_rounds as u64)
+            .ok_or(ProgramError::ArithmeticOverflow)?)
+    }
+
+    /// Lamports `vrf_fee_vault` must keep to pay `vrf_reserve_requests` more requests.
+    pub fn vrf_reserve_floor(&self, request_fee: u64) -> Result<u64> {
+        Ok(request_fee
+            .checked_mul(self.vrf_reserve_requests as u64)
+            .ok_or(ProgramError::ArithmeticOverflow)?)
+    }
+}
+
+/// Registry entry for an SPL mint that can be used to buy tickets
+#[account]
+#[derive(InitSpace, Debug)]
+pub struct SupportedToken {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub oracle: AssetOracle, // pinned token/USD feeds
+    pub enabled: bool,
+    pub min_tickets_per_purchase: u32,
+    pub max_tickets_per_purchase: u32,
+    pub bump: u8,
 }
 
 #[account]
@@ -1671,6 +2879,28 @@ pub struct ClientState {
     pub authority: Pubkey,
 }
 
+#[account]
+#[derive(InitSpace)]
+pub struct Roles {
+    #[max_len(MAX_ROLE_MEMBERS)]
+    pub members: Vec<RoleMember>,
+    pub bump: u8,
+}
+
+impl Roles {
+    pub fn has_role(&self, key: &Pubkey, role: u8) -> bool {
+        self.members
+            .iter()
+            .any(|m| m.member == *key && m.roles & role == role)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct RoleMember {
+    pub member: Pubkey,
+    pub roles: u8, // bitmask of ROLE_* bits
+}
+
 #[account]
 #[derive(InitSpace, Debug)]
 pub struct TokenRaffle {
@@ -1682,9 +2912,168 @@ pub struct TokenRaffle {
     pub total_rounds: u32,
     #[max_len(20)]
     pub pending_rounds: Vec<u32>,  // Rounds waiting to be processed by TukTuk and VRF
+    pub ticket_denomination: TicketDenomination, // applied to rounds created afterwards
+    pub price_guard: PriceGuard,
+    pub last_ticket_price: Option<u64>, // last price accepted by the price guard
+    pub last_ticket_price_at: i64,
+    pub circuit_breaker_tripped: bool, // purchases blocked until the authority resets it
+    pub accrued_commission: u64, // treasury ledger: commission of completed rounds not yet withdrawn
+    #[max_len(MAX_COMMISSION_RECIPIENTS)]
+    pub commission_balances: Vec<CommissionBalance>, // accrued_commission per recipient
+    pub unassigned_commission: u64, // part of accrued_commission credited while the ledger was full
+    pub outstanding_obligations: u64, // unclaimed prizes and commission the vault still has to pay out
     pub bump: u8,
 }
 
+impl TokenRaffle {
+    /// The SOL raffle is the `b"sol_raffle"` singleton and keeps a default `token_mint`;
+    /// SPL raffles are seeded with `b"token_raffle"` and their mint.
+    pub fn is_sol(&self) -> bool {
+        self.token_mint == Pubkey::default()
+    }
+
+    pub fn pda_seeds(&self) -> Vec<Vec<u8>> {
+        if self.is_sol() {
+            vec![b"sol_raffle".to_vec(), vec![self.bump]]
+        } else {
+            vec![
+                b"token_raffle".to_vec(),
+                self.token_mint.as_ref().to_vec(),
+                vec![self.bump],
+            ]
+        }
+    }
+
+    pub fn address(&self) -> Result<Pubkey> {
+        let seeds = self.pda_seeds();
+        let seeds: Vec<&[u8]> = seeds.iter().map(|s| s.as_slice()).collect();
+        Pubkey::create_program_address(&seeds, &crate::id())
+            .map_err(|_| RaffleError::InvalidTokenRaffle.into())
+    }
+
+    /// Adds `commission` to the treasury ledger, credited to `beneficiaries` by their shares.
+    /// Returns the amount credited to each, in the order of `beneficiaries`, and whether it
+    /// reached the recipient's balance. Shares of new recipients that find the ledger full go
+    /// to `unassigned_commission`, so settling a round never depends on free ledger slots.
+    pub fn credit_commission(
+        &mut self,
+        beneficiaries: &[BeneficiaryShare],
+        commission: u64,
+    ) -> Result<Vec<(u64, bool)>> {
+        let shares = split_commission(beneficiaries, commission)?;
+        let mut credited = Vec::with_capacity(shares.len());
+
+        for (share, &amount) in beneficiaries.iter().zip(shares.iter()) {
+            let assigned = amount == 0 || self.credit_recipient(&share.recipient, amount)?;
+            if !assigned {
+                self.unassigned_commission = self
+                    .unassigned_commission
+                    .checked_add(amount)
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+            }
+            credited.push((amount, assigned));
+        }
+
+        self.accrued_commission = self
+            .accrued_commission
+            .checked_add(commission)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(credited)
+    }
+
+    /// Moves `amount` of `unassigned_commission` to `recipient`'s balance and returns the new balance.
+    pub fn assign_commission(&mut self, recipient: &Pubkey, amount: u64) -> Result<u64> {
+        let unassigned_commission = self
+            .unassigned_commission
+            .checked_sub(amount)
+            .ok_or(RaffleError::InsufficientCommission)?;
+        require!(
+            self.credit_recipient(recipient, amount)?,
+            RaffleError::TooManyCommissionRecipients
+        );
+        self.unassigned_commission = unassigned_commission;
+
+        Ok(self.commission_balance(recipient))
+    }
+
+    pub fn commission_balance(&self, recipient: &Pubkey) -> u64 {
+        self.commission_balances
+            .iter()
+            .find(|balance| &balance.recipient == recipient)
+            .map_or(0, |balance| balance.amount)
+    }
+
+    /// Adds `amount` to `recipient`'s balance. Returns false, leaving the ledger untouched,
+    /// when the recipient has no balance yet and every slot is taken.
+    fn credit_recipient(&mut self, recipient: &Pubkey, amount: u64) -> Result<bool> {
+        match self
+            .commission_balances
+            .iter()
+            .position(|balance| &balance.recipient == recipient)
+        {
+            Some(index) => {
+                let balance = &mut self.commission_balances[index];
+                balance.amount = balance
+                    .amount
+                    .checked_add(amount)
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+            }
+            None if self.commission_balances.len() < MAX_COMMISSION_RECIPIENTS => {
+                self.commission_balances.push(CommissionBalance {
+                    recipient: *recipient,
+                    amount,
+                });
+            }
+            None => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Takes `amount` out of `recipient`'s commission balance and returns what is left.
+    /// A balance withdrawn in full frees its slot.
+    pub fn debit_commission(&mut self, recipient: &Pubkey, amount: u64) -> Result<u64> {
+        let index = self
+            .commission_balances
+            .iter()
+            .position(|balance| &balance.recipient == recipient)
+            .ok_or(RaffleError::InsufficientCommission)?;
+
+        let balance = &mut self.commission_balances[index];
+        balance.amount = balance
+            .amount
+            .checked_sub(amount)
+            .ok_or(RaffleError::InsufficientCommission)?;
+        let remaining = balance.amount;
+        if remaining == 0 {
+            self.commission_balances.remove(index);
+        }
+
+        self.accrued_commission = self
+            .accrued_commission
+            .checked_sub(amount)
+            .ok_or(RaffleError::AccountingMismatch)?;
+        Ok(remaining)
+    }
+
+    /// Records funds received into the vault that are owed to a winner or the treasury.
+    pub fn add_obligations(&mut self, amount: u64) -> Result<()> {
+        self.outstanding_obligations = self
+            .outstanding_obligations
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Records funds paid out of the vault against its obligations.
+    pub fn settle_obligations(&mut self, amount: u64) -> Result<()> {
+        self.outstanding_obligations = self
+            .outstanding_obligations
+            .checked_sub(amount)
+            .ok_or(RaffleError::AccountingMismatch)?;
+        Ok(())
+    }
+}
+
 #[account]
 #[derive(InitSpace, Debug)] 
 pub struct Round {
@@ -1696,12 +3085,19 @@ pub struct Round {
     pub end_time: i64,
     pub prize_amount: u64,
     pub commission_balance: u64,
+    pub total_received: u64,        // == prize_amount + commission_balance
+    pub commission_remainder: u16,  // sub-unit commission carried to the next purchase, in 1/10_000 units
     pub purchases_count: u32,
     pub total_tickets: u32,
     pub winner_ticket_index: Option<u32>,
     pub winner_purchase_index: Option<u32>,
     pub winner_address: Option<Pubkey>,
     pub prize_claimed: bool,
+    pub entrance_fee_bps: u16,       // snapshot of RaffleState at creation
+    #[max_len(MAX_BENEFICIARIES)]
+    pub beneficiaries: Vec<BeneficiaryShare>, // snapshot of RaffleState at creation, splits the round's commission
+    pub ticket_denomination: TicketDenomination, // snapshot of the raffle's denomination at creation
+    pub locked_ticket_price: Option<u64>, // ticket price in base units, set at creation when price lock is enabled
     pub bump: u8,
 }
 
@@ -1791,6 +3187,7 @@ pub struct RoundTicketsPurchase {
     pub player: Pubkey,
     pub purchase_index: u32,
     pub tickets_count: u32,
+    pub paid_amount: u64, // prize and commission share received from the player, refunded if the round is cancelled
     pub bump: u8,
 }
 
@@ -1812,10 +3209,88 @@ pub struct VRFContext {
     pub bump: u8,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace, Debug)]
-pub enum RoundStatus {
-    Open, // 0 - Round is open for tickets buying
-    Completed, // 1 - Round closed, winner picked
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace, Debug)]
+pub enum RoundStatus {
+    Open, // 0 - Round is open for tickets buying
+    Completed, // 1 - Round closed, winner picked
+    Cancelled, // 2 - Round aborted, every purchase can be refunded
+}
+
+/// Asset the ticket price is fixed in
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum TicketAsset {
+    Btc,    // 0 - satoshis, converted through BTC/USD and the pay asset's USD feed
+    Usd,    // 1 - micro-USD (6 decimals), converted through the pay asset's USD feed
+    Native, // 2 - base units of the pay asset, no oracle involved
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct TicketDenomination {
+    pub asset: TicketAsset,
+    pub amount: u64, // in the smallest unit of `asset`
+}
+
+impl TicketDenomination {
+    pub const DEFAULT: Self = Self {
+        asset: TicketAsset::Btc,
+        amount: DEFAULT_TICKET_BTC_SATOSHIS,
+    };
+
+    pub fn validate(&self) -> Result<()> {
+        require!(self.amount > 0, RaffleError::InvalidTicketDenomination);
+        Ok(())
+    }
+}
+
+/// Recipient of a basis-point share of the commission
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct BeneficiaryShare {
+    pub recipient: Pubkey, // wallet; for SPL raffles the owner of the receiving token account
+    pub share_bps: u16,
+}
+
+/// Commission accrued to one recipient and not yet withdrawn
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct CommissionBalance {
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// Sanity bounds and circuit breaker for ticket prices computed from the oracles
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct PriceGuard {
+    pub min_ticket_price: u64,   // base units of the pay asset
+    pub max_ticket_price: u64,   // base units of the pay asset
+    pub max_price_move_bps: u16, // 0 disables the circuit breaker
+    pub price_move_window: i64,  // seconds
+}
+
+impl PriceGuard {
+    /// Unconfigured: sane bounds depend on the pay asset's decimals, so the
+    /// authority must set them with `update_price_guard` before the first purchase.
+    pub const DEFAULT: Self = Self {
+        min_ticket_price: 0,
+        max_ticket_price: 0,
+        max_price_move_bps: 0,
+        price_move_window: 0,
+    };
+
+    /// `validate` never accepts a zero maximum, so only `DEFAULT` fails this.
+    pub fn is_configured(&self) -> bool {
+        self.max_ticket_price > 0
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.min_ticket_price > 0 && self.min_ticket_price <= self.max_ticket_price,
+            RaffleError::InvalidPriceGuard
+        );
+        require!(
+            self.max_price_move_bps == 0 || self.price_move_window > 0,
+            RaffleError::InvalidPriceGuard
+        );
+        Ok(())
+    }
 }
 
 // Events
@@ -1828,6 +3303,7 @@ pub struct TicketPurchased {
     pub total_amount: u64,
     pub prize_amount: u64,
     pub commission_amount: u64,
+    pub denomination: TicketDenomination, // what the ticket price was fixed in
     pub timestamp: i64,
 }
 
@@ -1849,6 +3325,113 @@ pub struct StatusChanged {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct PriceFeedsUpdated {
+    pub old_btc_oracle: AssetOracle,
+    pub old_sol_oracle: AssetOracle,
+    pub btc_oracle: AssetOracle,
+    pub sol_oracle: AssetOracle,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TicketDenominationUpdated {
+    pub token: Pubkey,
+    pub old_denomination: TicketDenomination,
+    pub denomination: TicketDenomination,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PriceGuardUpdated {
+    pub token: Pubkey,
+    pub old_price_guard: PriceGuard,
+    pub price_guard: PriceGuard,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CircuitBreakerTripped {
+    pub token: Pubkey,
+    pub last_ticket_price: u64,
+    pub ticket_price: u64,
+    pub move_bps: u64,
+    pub buyer: Pubkey, // purchase aborted by the trip: the transaction succeeds but buys no tickets
+    pub round_id: u32,
+    pub tickets_requested: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CircuitBreakerReset {
+    pub token: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EntranceFeeUpdated {
+    pub old_entrance_fee_bps: u16,
+    pub entrance_fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BeneficiariesUpdated {
+    pub old_beneficiaries: Vec<BeneficiaryShare>,
+    pub beneficiaries: Vec<BeneficiaryShare>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub old_authority: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub member: Pubkey,
+    pub roles: u8,        // bits granted
+    pub member_roles: u8, // roles held afterwards
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub member: Pubkey,
+    pub roles: u8,        // bits revoked
+    pub member_roles: u8, // roles held afterwards
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PauseChanged {
+    pub old_paused: u8,
+    pub paused: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PriceLockUpdated {
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct AllRequestsCompleted {}
 
@@ -1858,12 +3441,43 @@ pub struct PrizeClaimed {
     pub winner: Pubkey,
 }
 
-// Error codes
+#[event]
+pub struct CommissionAccrued {
+    pub token: Pubkey,
+    pub round_id: u32,
+    pub amount: u64,
+    pub accrued_commission: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CommissionWithdrawn {
+    pub token: Pubkey,
+    pub proposal_id: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub recipient_balance: u64, // left to withdraw for the recipient
+    pub accrued_commission: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CommissionShareAccrued {
+    pub token: Pubkey,
+    pub round_id: u32,
+    pub recipient: Pubkey,
+    pub share_bps: u16,
+    pub amount: u64,
+    pub assigned: bool, // false when the share went to unassigned_commission
+    pub timestamp: i64,
+}
+
+// Error codes are positional and part of the IDL: append new variants at the end
 #[error_code]
 pub enum RaffleError {
     #[msg("Prize already claimed")]
     AlreadyClaimed,
-    
+
     #[msg("All VRF request accounts is used")]
     AllRequestsCompleted,
 
@@ -1950,4 +3564,540 @@ pub enum RaffleError {
 
     #[msg("Winner address already set")]
     WinnerAlreadySet,
+
+    #[msg("Token mint does not match the token raffle")]
+    InvalidTokenMint,
+
+    #[msg("Invalid token raffle account")]
+    InvalidTokenRaffle,
+
+    #[msg("Invalid ticket limits")]
+    InvalidTicketLimits,
+
+    #[msg("Token is disabled for purchases")]
+    TokenDisabled,
+
+    #[msg("Invalid oracle configuration")]
+    InvalidOracleConfig,
+
+    #[msg("Oracle prices deviate beyond the configured threshold")]
+    OracleDeviationTooHigh,
+
+    #[msg("Oracle confidence interval exceeds the configured limit")]
+    OracleConfidenceTooWide,
+
+    #[msg("Invalid ticket denomination")]
+    InvalidTicketDenomination,
+
+    #[msg("Purchases paused by the price circuit breaker")]
+    CircuitBreakerTripped,
+
+    #[msg("Invalid price guard")]
+    InvalidPriceGuard,
+
+    #[msg("Ticket price outside the configured bounds")]
+    TicketPriceOutOfBounds,
+
+    #[msg("Recomputed cost deviates from the quote beyond the accepted threshold")]
+    QuoteDeviationTooHigh,
+
+    #[msg("Ticket quote has expired")]
+    QuoteExpired,
+
+    #[msg("Invalid pause scope")]
+    InvalidPauseScope,
+
+    #[msg("Operation is paused")]
+    Paused,
+
+    #[msg("Entrance fee exceeds the maximum")]
+    InvalidEntranceFee,
+
+    #[msg("Prize and commission do not add up to the amount received")]
+    AccountingMismatch,
+
+    #[msg("Beneficiary shares must be non-zero and sum to 10000 bps")]
+    InvalidBeneficiaryShares,
+
+    #[msg("Amount exceeds the accrued commission")]
+    InsufficientCommission,
+
+    #[msg("Invalid authority")]
+    InvalidAuthority,
+
+    #[msg("No authority transfer pending")]
+    NoPendingAuthority,
+
+    #[msg("Invalid role")]
+    InvalidRole,
+
+    #[msg("Too many role members")]
+    TooManyRoleMembers,
+
+    #[msg("Pending change not found")]
+    ChangeNotFound,
+
+    #[msg("Recipient does not match the proposer of the change")]
+    InvalidRecipient,
+
+    #[msg("Timelock delay out of range")]
+    InvalidTimelockDelay,
+
+    #[msg("Timelock delay has not elapsed yet")]
+    TimelockNotElapsed,
+
+    #[msg("Too many pending changes")]
+    TooManyPendingChanges,
+
+    #[msg("Invalid multisig signers or threshold")]
+    InvalidMultisigConfig,
+
+    #[msg("Proposal lifetime out of range")]
+    InvalidProposalLifetime,
+
+    #[msg("Not a multisig signer")]
+    NotMultisigSigner,
+
+    #[msg("Proposal already approved by this signer")]
+    ProposalAlreadyApproved,
+
+    #[msg("Proposal expired")]
+    ProposalExpired,
+
+    #[msg("Proposal does not match this instruction or the current signer set")]
+    ProposalMismatch,
+
+    #[msg("Not enough approvals")]
+    ThresholdNotReached,
+
+    #[msg("Withdrawal would leave the vault below its reserve floor")]
+    ReserveFloorBreached,
+
+    #[msg("Vault top-up share exceeds 10_000 bps")]
+    InvalidVaultTopUp,
+
+    #[msg("Round has been cancelled")]
+    RoundCancelled,
+
+    #[msg("Round has not been cancelled")]
+    RoundNotCancelled,
+
+    #[msg("Price guard must be configured before tickets can be bought")]
+    PriceGuardNotConfigured,
+
+    #[msg("Too many commission recipients with an outstanding balance")]
+    TooManyCommissionRecipients,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(crate) fn token_raffle(price_guard: PriceGuard) -> TokenRaffle {
+        TokenRaffle {
+            authority: Pubkey::new_unique(),
+            token_mint: Pubkey::default(),
+            current_round_id: None,
+            current_round_status: RoundStatus::Open,
+            current_round_end_time: None,
+            total_rounds: 0,
+            pending_rounds: Vec::new(),
+            ticket_denomination: TicketDenomination::DEFAULT,
+            price_guard,
+            last_ticket_price: None,
+            last_ticket_price_at: 0,
+            circuit_breaker_tripped: false,
+            accrued_commission: 0,
+            commission_balances: Vec::new(),
+            unassigned_commission: 0,
+            outstanding_obligations: 0,
+            bump: 255,
+        }
+    }
+
+    const GUARD: PriceGuard = PriceGuard {
+        min_ticket_price: 1_000,
+        max_ticket_price: 100_000,
+        max_price_move_bps: 1_000, // 10%
+        price_move_window: 60,
+    };
+
+    fn guard_price(token_raffle: &mut TokenRaffle, ticket_price: u64, current_time: i64) -> Result<bool> {
+        apply_price_guard(token_raffle, ticket_price, Pubkey::default(), 1, 1, current_time)
+    }
+
+    pub(crate) fn round(entrance_fee_bps: u16) -> Round {
+        Round {
+            initialized: true,
+            token_raffle: Pubkey::new_unique(),
+            round_id: 1,
+            status: RoundStatus::Open,
+            start_time: 0,
+            end_time: ROUND_DURATION,
+            prize_amount: 0,
+            commission_balance: 0,
+            total_received: 0,
+            commission_remainder: 0,
+            purchases_count: 0,
+            total_tickets: 0,
+            winner_ticket_index: None,
+            winner_purchase_index: None,
+            winner_address: None,
+            prize_claimed: false,
+            entrance_fee_bps,
+            beneficiaries: Vec::new(),
+            ticket_denomination: TicketDenomination::DEFAULT,
+            locked_ticket_price: None,
+            bump: 255,
+        }
+    }
+
+    fn share(share_bps: u16) -> BeneficiaryShare {
+        BeneficiaryShare {
+            recipient: Pubkey::new_unique(),
+            share_bps,
+        }
+    }
+
+    fn micro_usd(amount: u64) -> Decimal {
+        Decimal::from_i128_with_scale(amount as i128, USD_DECIMALS as u32)
+    }
+
+    #[test]
+    fn usd_converts_to_lamports() {
+        // $5 at $100/SOL = 0.05 SOL
+        let lamports = convert_usd_to_base_units(micro_usd(5_000_000), Decimal::from(100), SOL_DECIMALS).unwrap();
+        assert_eq!(lamports, 50_000_000);
+    }
+
+    #[test]
+    fn usd_converts_to_token_base_units() {
+        // $2.5 of a $1 stablecoin with 6 decimals
+        let units = convert_usd_to_base_units(micro_usd(2_500_000), Decimal::ONE, 6).unwrap();
+        assert_eq!(units, 2_500_000);
+    }
+
+    #[test]
+    fn base_units_round_to_nearest() {
+        // $1 at $3 = 333_333.33 units, $2 at $3 = 666_666.67 units
+        assert_eq!(convert_usd_to_base_units(micro_usd(1_000_000), Decimal::from(3), 6).unwrap(), 333_333);
+        assert_eq!(convert_usd_to_base_units(micro_usd(2_000_000), Decimal::from(3), 6).unwrap(), 666_667);
+    }
+
+    #[test]
+    fn btc_ticket_price_converts_through_usd() {
+        // 10_000 sats at $60_000/BTC = $6, at $150/SOL = 0.04 SOL
+        let usd = get_ticket_price_in_usd(10_000, Decimal::from(60_000)).unwrap();
+        assert_eq!(usd, Decimal::from(6));
+        assert_eq!(convert_usd_to_base_units(usd, Decimal::from(150), SOL_DECIMALS).unwrap(), 40_000_000);
+    }
+
+    #[test]
+    fn base_units_overflowing_u64_are_rejected() {
+        let result = convert_usd_to_base_units(Decimal::from(u64::MAX), Decimal::ONE, SOL_DECIMALS);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn price_guard_must_be_configured() {
+        let mut raffle = token_raffle(PriceGuard::DEFAULT);
+        assert_eq!(
+            guard_price(&mut raffle, 10_000, 0).unwrap_err(),
+            RaffleError::PriceGuardNotConfigured.into()
+        );
+    }
+
+    #[test]
+    fn price_guard_rejects_prices_out_of_bounds() {
+        let mut raffle = token_raffle(GUARD);
+        assert_eq!(guard_price(&mut raffle, 999, 0).unwrap_err(), RaffleError::TicketPriceOutOfBounds.into());
+        assert_eq!(guard_price(&mut raffle, 100_001, 0).unwrap_err(), RaffleError::TicketPriceOutOfBounds.into());
+        assert_eq!(raffle.last_ticket_price, None);
+    }
+
+    #[test]
+    fn price_guard_accepts_and_records_prices_within_bounds() {
+        let mut raffle = token_raffle(GUARD);
+        assert!(guard_price(&mut raffle, 10_000, 100).unwrap());
+        // Exactly 10% within the window is still accepted
+        assert!(guard_price(&mut raffle, 11_000, 130).unwrap());
+        assert_eq!(raffle.last_ticket_price, Some(11_000));
+        assert_eq!(raffle.last_ticket_price_at, 130);
+        assert!(!raffle.circuit_breaker_tripped);
+    }
+
+    #[test]
+    fn price_guard_trips_on_fast_moves() {
+        let mut raffle = token_raffle(GUARD);
+        assert!(guard_price(&mut raffle, 10_000, 100).unwrap());
+        assert!(!guard_price(&mut raffle, 8_999, 150).unwrap());
+        assert!(raffle.circuit_breaker_tripped);
+        // The tripping price is not accepted as the new reference
+        assert_eq!(raffle.last_ticket_price, Some(10_000));
+        assert_eq!(raffle.last_ticket_price_at, 100);
+    }
+
+    #[test]
+    fn price_guard_ignores_moves_outside_the_window() {
+        let mut raffle = token_raffle(GUARD);
+        assert!(guard_price(&mut raffle, 10_000, 100).unwrap());
+        assert!(guard_price(&mut raffle, 20_000, 161).unwrap());
+        assert!(!raffle.circuit_breaker_tripped);
+    }
+
+    #[test]
+    fn price_guard_without_breaker_only_checks_bounds() {
+        let mut raffle = token_raffle(PriceGuard { max_price_move_bps: 0, ..GUARD });
+        assert!(guard_price(&mut raffle, 10_000, 100).unwrap());
+        assert!(guard_price(&mut raffle, 90_000, 101).unwrap());
+        assert!(!raffle.circuit_breaker_tripped);
+    }
+
+    #[test]
+    fn purchase_splits_by_entrance_fee() {
+        let mut round = round(250); // 2.5%
+        assert_eq!(split_purchase(&mut round, 1_000_000).unwrap(), (975_000, 25_000));
+        assert_eq!(round.prize_amount, 975_000);
+        assert_eq!(round.commission_balance, 25_000);
+        assert_eq!(round.total_received, 1_000_000);
+        assert_eq!(round.commission_remainder, 0);
+    }
+
+    #[test]
+    fn purchase_without_fee_goes_to_the_prize() {
+        let mut round = round(0);
+        assert_eq!(split_purchase(&mut round, 12_345).unwrap(), (12_345, 0));
+        assert_eq!(round.commission_balance, 0);
+    }
+
+    #[test]
+    fn commission_remainder_carries_between_purchases() {
+        let mut round = round(250);
+        // 3 * 250 = 750 / 10_000 of a unit per purchase
+        for _ in 0..13 {
+            assert_eq!(split_purchase(&mut round, 3).unwrap(), (3, 0));
+        }
+        assert_eq!(round.commission_remainder, 9_750);
+        assert_eq!(split_purchase(&mut round, 3).unwrap(), (2, 1));
+        assert_eq!(round.commission_remainder, 500);
+        assert_eq!(round.commission_balance, 1);
+        assert_eq!(round.total_received, 42);
+    }
+
+    #[test]
+    fn purchase_split_keeps_round_totals_balanced() {
+        let mut round = round(1_337);
+        let mut total_paid: u64 = 0;
+
+        for cost in [1, 7, 999, 10_001, 123_456_789, 5, 1_000_000_000, 3] {
+            let (prize, commission) = split_purchase(&mut round, cost).unwrap();
+            assert_eq!(prize + commission, cost);
+            total_paid += cost;
+
+            assert_eq!(round.total_received, total_paid);
+            assert_eq!(round.prize_amount + round.commission_balance, total_paid);
+            // Rounded down per round, the remainder holds the rest
+            let fee_numerator = total_paid as u128 * 1_337;
+            assert_eq!(round.commission_balance as u128, fee_numerator / 10_000);
+            assert_eq!(round.commission_remainder as u128, fee_numerator % 10_000);
+        }
+    }
+
+    #[test]
+    fn commission_splits_by_shares() {
+        let beneficiaries = [share(7_000), share(2_000), share(1_000)];
+        assert_eq!(split_commission(&beneficiaries, 1_000_000).unwrap(), vec![700_000, 200_000, 100_000]);
+    }
+
+    #[test]
+    fn commission_split_dust_goes_to_the_first_recipient() {
+        let beneficiaries = [share(3_334), share(3_333), share(3_333)];
+        // 33.34 + 33.33 + 33.33 rounded down = 33 + 33 + 33, 1 unit of dust
+        assert_eq!(split_commission(&beneficiaries, 100).unwrap(), vec![34, 33, 33]);
+    }
+
+    #[test]
+    fn commission_split_adds_up_to_the_commission() {
+        let beneficiaries = [share(1), share(4_999), share(2_500), share(1_250), share(1_250)];
+        for commission in [0, 1, 9, 10_001, 987_654_321, u64::MAX] {
+            let amounts = split_commission(&beneficiaries, commission).unwrap();
+            assert_eq!(amounts.len(), beneficiaries.len());
+            assert_eq!(amounts.iter().map(|&a| a as u128).sum::<u128>(), commission as u128);
+        }
+    }
+
+    #[test]
+    fn beneficiaries_are_validated() {
+        assert!(validate_beneficiaries(&[share(6_000), share(4_000)]).is_ok());
+        assert_eq!(validate_beneficiaries(&[]).unwrap_err(), RaffleError::InvalidBeneficiary.into());
+        assert_eq!(
+            validate_beneficiaries(&[share(6_000), share(3_999)]).unwrap_err(),
+            RaffleError::InvalidBeneficiaryShares.into()
+        );
+        assert_eq!(
+            validate_beneficiaries(&[share(10_000), share(0)]).unwrap_err(),
+            RaffleError::InvalidBeneficiaryShares.into()
+        );
+
+        let duplicate = share(5_000);
+        assert_eq!(
+            validate_beneficiaries(&[duplicate, duplicate]).unwrap_err(),
+            RaffleError::InvalidBeneficiary.into()
+        );
+        assert_eq!(
+            validate_beneficiaries(&[share(2_000); MAX_BENEFICIARIES + 1]).unwrap_err(),
+            RaffleError::InvalidBeneficiary.into()
+        );
+    }
+
+    #[test]
+    fn commission_is_credited_per_recipient() {
+        let mut raffle = token_raffle(GUARD);
+        let (a, b) = (share(7_000), share(3_000));
+
+        assert_eq!(raffle.credit_commission(&[a, b], 1_001).unwrap(), vec![(701, true), (300, true)]);
+        raffle.credit_commission(&[b, a], 100).unwrap();
+
+        assert_eq!(raffle.accrued_commission, 1_101);
+        assert_eq!(
+            raffle.commission_balances,
+            vec![
+                CommissionBalance { recipient: a.recipient, amount: 771 },
+                CommissionBalance { recipient: b.recipient, amount: 330 },
+            ]
+        );
+    }
+
+    #[test]
+    fn commission_debit_frees_emptied_balances() {
+        let mut raffle = token_raffle(GUARD);
+        let (a, b) = (share(5_000), share(5_000));
+        raffle.credit_commission(&[a, b], 1_000).unwrap();
+
+        assert_eq!(raffle.debit_commission(&a.recipient, 200).unwrap(), 300);
+        assert_eq!(
+            raffle.debit_commission(&a.recipient, 301).unwrap_err(),
+            RaffleError::InsufficientCommission.into()
+        );
+        assert_eq!(raffle.debit_commission(&a.recipient, 300).unwrap(), 0);
+        assert_eq!(
+            raffle.debit_commission(&a.recipient, 1).unwrap_err(),
+            RaffleError::InsufficientCommission.into()
+        );
+
+        assert_eq!(raffle.accrued_commission, 500);
+        assert_eq!(raffle.commission_balances, vec![CommissionBalance { recipient: b.recipient, amount: 500 }]);
+    }
+
+    #[test]
+    fn commission_recipients_are_capped() {
+        let mut raffle = token_raffle(GUARD);
+        for _ in 0..MAX_COMMISSION_RECIPIENTS {
+            raffle.credit_commission(&[share(10_000)], 10).unwrap();
+        }
+
+        // Settlement still succeeds; the new recipient's share waits to be assigned
+        let late = share(10_000);
+        assert_eq!(raffle.credit_commission(&[late], 10).unwrap(), vec![(10, false)]);
+        assert_eq!(raffle.accrued_commission, 10 * (MAX_COMMISSION_RECIPIENTS as u64 + 1));
+        assert_eq!(raffle.unassigned_commission, 10);
+        assert_eq!(
+            raffle.assign_commission(&late.recipient, 10).unwrap_err(),
+            RaffleError::TooManyCommissionRecipients.into()
+        );
+
+        // A withdrawn balance frees its slot
+        let first = raffle.commission_balances[0].recipient;
+        raffle.debit_commission(&first, 10).unwrap();
+        assert_eq!(
+            raffle.assign_commission(&late.recipient, 11).unwrap_err(),
+            RaffleError::InsufficientCommission.into()
+        );
+        assert_eq!(raffle.assign_commission(&late.recipient, 10).unwrap(), 10);
+        assert_eq!(raffle.unassigned_commission, 0);
+        assert_eq!(raffle.commission_balances.len(), MAX_COMMISSION_RECIPIENTS);
+    }
+
+    #[test]
+    fn settlement_survives_beneficiary_rotations() {
+        let mut raffle = token_raffle(GUARD);
+
+        // Every rotation brings a new set that keeps earning while the old balances wait
+        for _ in 0..4 {
+            let beneficiaries = [share(5_000), share(5_000)];
+            raffle.credit_commission(&beneficiaries, 1_000).unwrap();
+        }
+        let rotated = [share(6_000), share(4_000)];
+        assert_eq!(
+            raffle.credit_commission(&rotated, 1_000).unwrap(),
+            vec![(600, true), (400, true)]
+        );
+
+        // The ledger is full: only recipients that already hold a balance get credited directly
+        let next = [share(6_000), rotated[1]];
+        assert_eq!(
+            raffle.credit_commission(&next, 1_000).unwrap(),
+            vec![(600, false), (400, true)]
+        );
+
+        assert_eq!(raffle.accrued_commission, 6_000);
+        assert_eq!(raffle.unassigned_commission, 600);
+        assert_eq!(raffle.commission_balance(&rotated[1].recipient), 800);
+        assert_eq!(raffle.commission_balance(&next[0].recipient), 0);
+    }
+
+    fn quote(btc_oracle_slot: Option<u64>, sol_oracle_slot: Option<u64>) -> TicketQuote {
+        TicketQuote {
+            count: 1,
+            ticket_price: 1_000,
+            total_cost: 1_000,
+            denomination: TicketDenomination::DEFAULT,
+            locked: false,
+            btc_oracle_slot,
+            sol_oracle_slot,
+            quoted_slot: 0,
+            expiry_slot: u64::MAX,
+        }
+    }
+
+    #[test]
+    fn quote_expires_from_its_oracle_slots() {
+        let read = TicketPrice {
+            amount: 1_000,
+            btc_oracle_slot: Some(1_010),
+            pay_oracle_slot: Some(1_020),
+        };
+        let fresh = quote(Some(1_000), Some(1_005));
+
+        // expiry_slot is ignored, the oldest oracle slot bounds the quote
+        check_quote_expiry(&fresh, &read, 1_000 + QUOTE_VALIDITY_SLOTS).unwrap();
+        assert_eq!(
+            check_quote_expiry(&fresh, &read, 1_001 + QUOTE_VALIDITY_SLOTS).unwrap_err(),
+            RaffleError::QuoteExpired.into()
+        );
+    }
+
+    #[test]
+    fn quote_oracle_slots_cannot_outrun_the_feeds() {
+        let read = TicketPrice {
+            amount: 1_000,
+            btc_oracle_slot: Some(1_010),
+            pay_oracle_slot: Some(1_020),
+        };
+
+        for forged in [quote(Some(u64::MAX), Some(1_020)), quote(Some(1_010), None), quote(None, None)] {
+            assert_eq!(
+                check_quote_expiry(&forged, &read, 1_030).unwrap_err(),
+                RaffleError::QuoteExpired.into()
+            );
+        }
+
+        // Locked and test prices read no feed
+        let locked = TicketPrice {
+            amount: 1_000,
+            btc_oracle_slot: None,
+            pay_oracle_slot: None,
+        };
+        check_quote_expiry(&quote(None, None), &locked, u64::MAX).unwrap();
+    }
 }
@@ -0,0 +1,684 @@
+use anchor_lang::prelude::*;
+use orao_solana_vrf_cb::{state::network_state::NetworkState, CB_CONFIG_ACCOUNT_SEED};
+use crate::{
+    RaffleError, RaffleState, Roles, TokenRaffle, MAX_MULTISIG_SIGNERS, MAX_PROPOSAL_LIFETIME,
+    PAUSE_WITHDRAWALS, ROLE_TREASURER,
+};
+
+pub fn initialize_multisig(
+    ctx: Context<InitializeMultisig>,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    validate_signer_set(&signers, threshold)?;
+
+    let multisig = &mut ctx.accounts.multisig;
+    multisig.signers = signers;
+    multisig.threshold = threshold;
+    multisig.signer_set_version = 0;
+    multisig.next_proposal_id = 0;
+    multisig.bump = ctx.bumps.multisig;
+
+    msg!(
+        "Multisig initialized: {} of {} signers",
+        multisig.threshold,
+        multisig.signers.len()
+    );
+
+    Ok(())
+}
+
+/// Withdrawals can be proposed by a signer or a treasurer, signer set changes
+/// only by a signer. A proposing signer's approval is counted right away.
+/// Timelocked proposals get their `lifetime` once `RaffleState::timelock_delay` has elapsed.
+pub fn create_proposal(
+    ctx: Context<CreateProposal>,
+    action: MultisigAction,
+    lifetime: i64,
+) -> Result<()> {
+    let proposer = ctx.accounts.proposer.key();
+    let multisig = &mut ctx.accounts.multisig;
+    let is_signer = multisig.is_signer(&proposer);
+
+    let authorized = match action {
+        MultisigAction::UpdateSigners { .. } => is_signer,
+        _ => {
+            is_signer
+                || ctx.accounts.raffle_state.has_role(
+                    ctx.accounts.roles.as_deref(),
+                    &proposer,
+                    ROLE_TREASURER,
+                )
+        }
+    };
+    require!(authorized, RaffleError::Unauthorized);
+    action.validate()?;
+    require!(
+        lifetime > 0 && lifetime <= MAX_PROPOSAL_LIFETIME,
+        RaffleError::InvalidProposalLifetime
+    );
+
+    let created_at = Clock::get()?.unix_timestamp;
+    let executable_after = if action.is_timelocked() {
+        created_at
+            .checked_add(ctx.accounts.raffle_state.timelock_delay)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+    } else {
+        created_at
+    };
+    let proposal_id = multisig.next_proposal_id;
+    multisig.next_proposal_id += 1;
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.proposal_id = proposal_id;
+    proposal.action = action.clone();
+    proposal.proposer = proposer;
+    proposal.approvals = if is_signer { vec![proposer] } else { Vec::new() };
+    proposal.signer_set_version = multisig.signer_set_version;
+    proposal.created_at = created_at;
+    proposal.expires_at = executable_after + lifetime;
+    proposal.bump = ctx.bumps.proposal;
+
+    msg!("Proposal {} created: {:?}", proposal_id, action);
+
+    emit!(ProposalCreated {
+        proposal_id,
+        action,
+        proposer,
+        expires_at: proposal.expires_at,
+        timestamp: created_at,
+    });
+
+    Ok(())
+}
+
+pub fn approve_proposal(ctx: Context<ApproveProposal>, proposal_id: u64) -> Result<()> {
+    let multisig = &ctx.accounts.multisig;
+    let proposal = &mut ctx.accounts.proposal;
+    let signer = ctx.accounts.signer.key();
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(multisig.is_signer(&signer), RaffleError::NotMultisigSigner);
+    require!(
+        proposal.signer_set_version == multisig.signer_set_version,
+        RaffleError::ProposalMismatch
+    );
+    require!(now < proposal.expires_at, RaffleError::ProposalExpired);
+    require!(
+        !proposal.approvals.contains(&signer),
+        RaffleError::ProposalAlreadyApproved
+    );
+
+    proposal.approvals.push(signer);
+
+    msg!(
+        "Proposal {} approved by {} ({}/{})",
+        proposal_id,
+        signer,
+        proposal.approvals.len(),
+        multisig.threshold
+    );
+
+    emit!(ProposalApproved {
+        proposal_id,
+        signer,
+        approvals: proposal.approvals.len() as u8,
+        threshold: multisig.threshold,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// The proposer can cancel at any time, anyone else once the proposal expired.
+/// Rent goes back to the proposer.
+pub fn cancel_proposal(ctx: Context<CancelProposal>, proposal_id: u64) -> Result<()> {
+    let proposal = &ctx.accounts.proposal;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(
+        ctx.accounts.caller.key() == proposal.proposer || now >= proposal.expires_at,
+        RaffleError::Unauthorized
+    );
+
+    msg!("Proposal {} cancelled", proposal_id);
+
+    emit!(ProposalCancelled {
+        proposal_id,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Vault withdrawals are timelocked: they execute only once `RaffleState::timelock_delay`
+/// has elapsed since the proposal was created.
+/// The vault must keep its reserve floor afterwards: `RaffleState::rent_reserve_floor`
+/// for `rent_vault`, `RaffleState::vrf_reserve_floor` at the current ORAO fee for `vrf_fee_vault`.
+pub fn execute_vault_withdrawal(ctx: Context<ExecuteVaultWithdrawal>, proposal_id: u64) -> Result<()> {
+    ctx.accounts.raffle_state.require_not_paused(PAUSE_WITHDRAWALS)?;
+    let now = Clock::get()?.unix_timestamp;
+    let proposal = &ctx.accounts.proposal;
+    require_executable(&ctx.accounts.multisig, proposal, now)?;
+
+    let raffle_state = &ctx.accounts.raffle_state;
+    let executable_after = proposal
+        .created_at
+        .checked_add(raffle_state.timelock_delay)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    require!(now >= executable_after, RaffleError::TimelockNotElapsed);
+
+    let (vault, vault_seed, vault_bump, amount, recipient, floor) = match proposal.action {
+        MultisigAction::WithdrawVrfVault { amount, recipient } => {
+            let request_fee = ctx.accounts.network_state.config.request_fee;
+            (
+                ctx.accounts.vrf_fee_vault.to_account_info(),
+                b"vrf_fee_vault".as_ref(),
+                ctx.bumps.vrf_fee_vault,
+                amount,
+                recipient,
+                raffle_state.vrf_reserve_floor(request_fee)?,
+            )
+        }
+        MultisigAction::WithdrawRentVault { amount, recipient } => (
+            ctx.accounts.rent_vault.to_account_info(),
+            b"rent_vault".as_ref(),
+            ctx.bumps.rent_vault,
+            amount,
+            recipient,
+            raffle_state.rent_reserve_floor(&Rent::get()?)?,
+        ),
+        _ => return err!(RaffleError::ProposalMismatch),
+    };
+    require_keys_eq!(ctx.accounts.recipient.key(), recipient, RaffleError::InvalidRecipient);
+    let remaining = vault
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(RaffleError::InsufficientFunds)?;
+    require!(remaining >= floor, RaffleError::ReserveFloorBreached);
+
+    // Both vaults are owned by the system program, so lamports leave them through a signed transfer
+    anchor_lang::system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: vault.clone(),
+                to: ctx.accounts.recipient.to_account_info(),
+            },
+            &[&[vault_seed, &[vault_bump]]],
+        ),
+        amount,
+    )?;
+
+    msg!("Withdrawn {} lamports from {} to {}", amount, vault.key(), recipient);
+
+    emit!(ProposalExecuted {
+        proposal_id,
+        action: proposal.action.clone(),
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Bumps the signer set version, so approvals collected under the old set no longer count.
+pub fn execute_signers_update(ctx: Context<ExecuteSignersUpdate>, proposal_id: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let proposal = &ctx.accounts.proposal;
+    require_executable(&ctx.accounts.multisig, proposal, now)?;
+
+    let MultisigAction::UpdateSigners { signers, threshold } = proposal.action.clone() else {
+        return err!(RaffleError::ProposalMismatch);
+    };
+    validate_signer_set(&signers, threshold)?;
+
+    let multisig = &mut ctx.accounts.multisig;
+
+    emit!(MultisigSignersUpdated {
+        old_signers: multisig.signers.clone(),
+        old_threshold: multisig.threshold,
+        signers: signers.clone(),
+        threshold,
+        timestamp: now,
+    });
+
+    multisig.signers = signers;
+    multisig.threshold = threshold;
+    multisig.signer_set_version += 1;
+
+    msg!(
+        "✅ Multisig signers updated: {} of {}",
+        threshold,
+        multisig.signers.len()
+    );
+
+    emit!(ProposalExecuted {
+        proposal_id,
+        action: proposal.action.clone(),
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Moves commission credited while the ledger was full to the recipient it was meant for,
+/// once a withdrawal has freed a slot.
+pub fn execute_commission_assignment(
+    ctx: Context<ExecuteCommissionAssignment>,
+    proposal_id: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let proposal = &ctx.accounts.proposal;
+    require_executable(&ctx.accounts.multisig, proposal, now)?;
+
+    let MultisigAction::AssignCommission { token_mint, amount, recipient } = proposal.action else {
+        return err!(RaffleError::ProposalMismatch);
+    };
+    let token_raffle = &mut ctx.accounts.token_raffle;
+    require_keys_eq!(token_mint, token_raffle.token_mint, RaffleError::ProposalMismatch);
+
+    let recipient_balance = token_raffle.assign_commission(&recipient, amount)?;
+
+    msg!(
+        "✅ Commission assigned: {} to {}, {} left unassigned",
+        amount,
+        recipient,
+        token_raffle.unassigned_commission
+    );
+
+    emit!(CommissionAssigned {
+        token: token_mint,
+        proposal_id,
+        recipient,
+        amount,
+        recipient_balance,
+        unassigned_commission: token_raffle.unassigned_commission,
+        timestamp: now,
+    });
+
+    emit!(ProposalExecuted {
+        proposal_id,
+        action: proposal.action.clone(),
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Checks that `proposal` was approved by `threshold` signers of the current set and has not expired.
+pub fn require_executable(multisig: &Multisig, proposal: &Proposal, now: i64) -> Result<()> {
+    require!(
+        proposal.signer_set_version == multisig.signer_set_version,
+        RaffleError::ProposalMismatch
+    );
+    require!(now < proposal.expires_at, RaffleError::ProposalExpired);
+    require!(
+        proposal.approvals.len() >= multisig.threshold as usize,
+        RaffleError::ThresholdNotReached
+    );
+    Ok(())
+}
+
+fn validate_signer_set(signers: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !signers.is_empty() && signers.len() <= MAX_MULTISIG_SIGNERS,
+        RaffleError::InvalidMultisigConfig
+    );
+    require!(
+        threshold > 0 && threshold as usize <= signers.len(),
+        RaffleError::InvalidMultisigConfig
+    );
+    for (i, signer) in signers.iter().enumerate() {
+        require_keys_neq!(*signer, Pubkey::default(), RaffleError::InvalidMultisigConfig);
+        require!(!signers[..i].contains(signer), RaffleError::InvalidMultisigConfig);
+    }
+    Ok(())
+}
+
+/// Action executed once a proposal collects `threshold` approvals
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace, Debug)]
+pub enum MultisigAction {
+    WithdrawVrfVault {
+        amount: u64,
+        recipient: Pubkey,
+    },
+    WithdrawRentVault {
+        amount: u64,
+        recipient: Pubkey,
+    },
+    WithdrawCommissionSol {
+        amount: u64, // up to the recipient's commission balance
+        recipient: Pubkey,
+    },
+    WithdrawCommissionSpl {
+        token_mint: Pubkey,
+        amount: u64, // up to the recipient's commission balance
+        recipient: Pubkey,
+    },
+    UpdateSigners {
+        #[max_len(MAX_MULTISIG_SIGNERS)]
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    },
+    AssignCommission {
+        token_mint: Pubkey, // default for the SOL raffle
+        amount: u64, // up to the raffle's unassigned commission
+        recipient: Pubkey,
+    },
+}
+
+impl MultisigAction {
+    /// Vault withdrawals also wait for `RaffleState::timelock_delay` after creation
+    pub fn is_timelocked(&self) -> bool {
+        matches!(self, Self::WithdrawVrfVault { .. } | Self::WithdrawRentVault { .. })
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::WithdrawVrfVault { amount, recipient }
+            | Self::WithdrawRentVault { amount, recipient }
+            | Self::WithdrawCommissionSol { amount, recipient }
+            | Self::WithdrawCommissionSpl { amount, recipient, .. }
+            | Self::AssignCommission { amount, recipient, .. } => {
+                require!(*amount > 0, RaffleError::InvalidAmount);
+                require_keys_neq!(*recipient, Pubkey::default(), RaffleError::InvalidRecipient);
+                Ok(())
+            }
+            Self::UpdateSigners { signers, threshold } => validate_signer_set(signers, *threshold),
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Multisig {
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub signer_set_version: u32, // bumped on every signer set change
+    pub next_proposal_id: u64,
+    pub bump: u8,
+}
+
+impl Multisig {
+    pub fn is_signer(&self, key: &Pubkey) -> bool {
+        self.signers.contains(key)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub proposal_id: u64,
+    pub action: MultisigAction,
+    pub proposer: Pubkey, // pays the rent and gets it back once the proposal is closed
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub approvals: Vec<Pubkey>,
+    pub signer_set_version: u32,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+// Account validation structs
+#[derive(Accounts)]
+pub struct InitializeMultisig<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        has_one = authority @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Multisig::INIT_SPACE,
+        seeds = [b"multisig"],
+        bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    /// Optional: without it only multisig signers can propose
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [b"proposal", &multisig.next_proposal_id.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ApproveProposal<'info> {
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", &proposal_id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct CancelProposal<'info> {
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"proposal", &proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer @ RaffleError::Unauthorized
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: receives the proposal rent, matched by `has_one`
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ExecuteVaultWithdrawal<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"proposal", &proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer @ RaffleError::Unauthorized
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: receives the proposal rent, matched by `has_one`
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vrf_fee_vault"],
+        bump
+    )]
+    pub vrf_fee_vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"rent_vault"],
+        bump
+    )]
+    pub rent_vault: SystemAccount<'info>,
+
+    /// ORAO network config, read for the current request fee
+    #[account(
+        seeds = [CB_CONFIG_ACCOUNT_SEED],
+        seeds::program = orao_solana_vrf_cb::id(),
+        bump = network_state.bump,
+    )]
+    pub network_state: Account<'info, NetworkState>,
+
+    /// CHECK: must match the recipient of the proposal
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ExecuteSignersUpdate<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"proposal", &proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer @ RaffleError::Unauthorized
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: receives the proposal rent, matched by `has_one`
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ExecuteCommissionAssignment<'info> {
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"proposal", &proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer @ RaffleError::Unauthorized
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: receives the proposal rent, matched by `has_one`
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// The SOL raffle or an SPL raffle, matched against the proposal's `token_mint`
+    #[account(
+        mut,
+        constraint = token_raffle.key() == token_raffle.address()? @ RaffleError::InvalidTokenRaffle
+    )]
+    pub token_raffle: Account<'info, TokenRaffle>,
+}
+
+// Events
+#[event]
+pub struct ProposalCreated {
+    pub proposal_id: u64,
+    pub action: MultisigAction,
+    pub proposer: Pubkey,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal_id: u64,
+    pub signer: Pubkey,
+    pub approvals: u8,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalCancelled {
+    pub proposal_id: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal_id: u64,
+    pub action: MultisigAction,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MultisigSignersUpdated {
+    pub old_signers: Vec<Pubkey>,
+    pub old_threshold: u8,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CommissionAssigned {
+    pub token: Pubkey,
+    pub proposal_id: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub recipient_balance: u64,
+    pub unassigned_commission: u64,
+    pub timestamp: i64,
+}
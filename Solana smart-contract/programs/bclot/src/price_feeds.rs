@@ -1,89 +1,252 @@
 // price_feeds.rs - Price feed integration for Switchboard and Pyth
 use anchor_lang::prelude::*;
+use rust_decimal::Decimal;
+use crate::RaffleError;
+
+/// Oracle program a price feed account belongs to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum OracleSource {
+    Switchboard, // 0 - Switchboard On-Demand pull feed
+    Pyth,        // 1 - Pyth pull oracle price update
+}
+
+/// Pinned price feed account, the oracle it must be read with and its acceptance limits
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct PriceFeed {
+    pub address: Pubkey,
+    pub source: OracleSource,
+    pub max_staleness: u64,      // slots for Switchboard, seconds for Pyth
+    pub min_samples: u32,        // Switchboard only
+    pub max_confidence_bps: u16, // confidence interval relative to the price
+}
+
+impl PriceFeed {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.max_staleness > 0, RaffleError::InvalidOracleConfig);
+        require!(
+            self.source != OracleSource::Switchboard || self.min_samples > 0,
+            RaffleError::InvalidOracleConfig
+        );
+        require!(
+            self.max_confidence_bps > 0 && self.max_confidence_bps <= BPS_DENOMINATOR,
+            RaffleError::InvalidOracleConfig
+        );
+        Ok(())
+    }
+}
+
+/// Primary and secondary oracle for one asset. The secondary is used when the
+/// primary is stale or unreadable; when both are fresh they must agree within
+/// `max_deviation_bps` of the primary price.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct AssetOracle {
+    pub primary: PriceFeed,
+    pub secondary: PriceFeed,
+    pub max_deviation_bps: u16,
+}
+
+impl AssetOracle {
+    pub fn validate(&self) -> Result<()> {
+        self.primary.validate()?;
+        self.secondary.validate()?;
+        require!(
+            self.max_deviation_bps > 0 && self.max_deviation_bps <= BPS_DENOMINATOR,
+            RaffleError::InvalidOracleConfig
+        );
+        Ok(())
+    }
+}
+
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// USD price read from a feed and the slot of the oracle update it came from
+#[derive(Clone, Copy, Debug)]
+pub struct OraclePrice {
+    pub price: Decimal,
+    pub slot: u64,
+}
+
+/// USD price of an asset from its primary feed, falling back to the secondary.
+pub fn get_asset_price(
+    oracle: &AssetOracle,
+    primary_account: &AccountInfo,
+    secondary_account: &AccountInfo,
+    label: &str,
+) -> Result<OraclePrice> {
+    let primary = get_price(&oracle.primary, primary_account, label).ok();
+    let secondary = get_price(&oracle.secondary, secondary_account, label).ok();
+
+    match (primary, secondary) {
+        (Some(primary_price), Some(secondary_price)) => {
+            let deviation_bps = primary_price
+                .price
+                .checked_sub(secondary_price.price)
+                .ok_or(ProgramError::ArithmeticOverflow)?
+                .abs()
+                .checked_mul(Decimal::from(BPS_DENOMINATOR))
+                .ok_or(ProgramError::ArithmeticOverflow)?
+                .checked_div(primary_price.price)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+
+            msg!("{} oracle deviation: {} bps", label, deviation_bps);
+
+            require!(
+                deviation_bps <= Decimal::from(oracle.max_deviation_bps),
+                RaffleError::OracleDeviationTooHigh
+            );
+
+            Ok(primary_price)
+        }
+        (Some(primary_price), None) => {
+            msg!("⚠️ {} secondary oracle unavailable, using primary", label);
+            Ok(primary_price)
+        }
+        (None, Some(secondary_price)) => {
+            msg!("⚠️ {} primary oracle unavailable, falling back to secondary", label);
+            Ok(secondary_price)
+        }
+        (None, None) => err!(RaffleError::OracleError),
+    }
+}
+
+/// Reads the USD price from `feed_account` with the oracle selected in `feed`,
+/// rejecting it when it is stale or its confidence interval is too wide.
+pub fn get_price(feed: &PriceFeed, feed_account: &AccountInfo, label: &str) -> Result<OraclePrice> {
+    require_keys_eq!(feed_account.key(), feed.address, RaffleError::InvalidFeedAccount);
+
+    let (price, confidence, slot) = match feed.source {
+        OracleSource::Switchboard => switchboard::get_price(feed_account, feed, label)?,
+        OracleSource::Pyth => pyth::get_price(feed_account, feed, label)?,
+    };
+
+    require!(price > Decimal::ZERO, RaffleError::OracleError);
+
+    let confidence_bps = confidence
+        .checked_mul(Decimal::from(BPS_DENOMINATOR))
+        .ok_or(ProgramError::ArithmeticOverflow)?
+        .checked_div(price)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    msg!("{} confidence: {} bps", label, confidence_bps);
+
+    require!(
+        confidence_bps <= Decimal::from(feed.max_confidence_bps),
+        RaffleError::OracleConfidenceTooWide
+    );
+
+    Ok(OraclePrice { price, slot })
+}
 
 pub mod switchboard {
     use super::*;
-    
-    #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-    pub struct SwitchboardDecimal {
-        pub mantissa: i128,
-        pub scale: u32,
+    use switchboard_on_demand::on_demand::accounts::pull_feed::PullFeedAccountData;
+
+    /// Returns the feed value, its standard deviation and the slot of the result.
+    pub fn get_price(price_feed: &AccountInfo, feed: &PriceFeed, label: &str) -> Result<(Decimal, Decimal, u64)> {
+        let clock = Clock::get()?;
+        msg!("Parsing {} Switchboard feed at slot {}...", label, clock.slot);
+
+        let data = price_feed.data.borrow();
+
+        let pull_feed = PullFeedAccountData::parse(data).map_err(|e| {
+            msg!("{} Switchboard parse failed: {:?}", label, e);
+            RaffleError::InvalidFeedAccount
+        })?;
+
+        let price = pull_feed
+            .get_value(clock.slot, feed.max_staleness, feed.min_samples, false)
+            .map_err(|e| {
+                msg!("{} Switchboard get_value failed: {:?}", label, e);
+                RaffleError::OracleError
+            })?;
+
+        let std_dev = pull_feed.std_dev().ok_or(RaffleError::OracleError)?;
+
+        msg!("{} Price (Decimal): {} ± {}", label, price, std_dev);
+
+        Ok((price, std_dev, pull_feed.result.slot))
     }
-    
-    impl SwitchboardDecimal {
-        pub fn to_f64(&self) -> f64 {
-            (self.mantissa as f64) / (10_f64.powi(self.scale as i32))
-        }
+}
+
+pub mod pyth {
+    use super::*;
+
+    /// Pyth Solana Receiver program, owner of every `PriceUpdateV2` account
+    pub const RECEIVER_PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+    /// Anchor discriminator of `PriceUpdateV2`
+    pub const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+    pub enum VerificationLevel {
+        Partial { num_signatures: u8 },
+        Full,
     }
-    
-    #[account(zero_copy)]
-    pub struct AggregatorAccountData {
-        pub latest_confirmed_round: AggregatorRound,
-        // ... other fields
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+    pub struct PriceFeedMessage {
+        pub feed_id: [u8; 32],
+        pub price: i64,
+        pub conf: u64,
+        pub exponent: i32,
+        pub publish_time: i64,
+        pub prev_publish_time: i64,
+        pub ema_price: i64,
+        pub ema_conf: u64,
     }
-    
-    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
-    pub struct AggregatorRound {
-        pub result: SwitchboardDecimal,
-        pub round_open_timestamp: i64,
-        // ... other fields
+
+    /// Layout of the Pyth receiver `PriceUpdateV2` account (without discriminator)
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+    pub struct PriceUpdateV2 {
+        pub write_authority: Pubkey,
+        pub verification_level: VerificationLevel,
+        pub price_message: PriceFeedMessage,
+        pub posted_slot: u64,
     }
-    
-    pub fn get_price_from_feed(price_feed: &AccountInfo) -> Result<f64> {
-        require!(price_feed.data_len() >= 8, crate::LotteryError::InvalidPriceFeed);
-        
+
+    pub fn load_price_update(price_feed: &AccountInfo) -> Result<PriceUpdateV2> {
+        require_keys_eq!(*price_feed.owner, RECEIVER_PROGRAM_ID, RaffleError::InvalidFeedAccount);
+
         let data = price_feed.try_borrow_data()?;
-        let aggregator = bytemuck::from_bytes::<AggregatorAccountData>(&data[8..]);
-        
-        // Check staleness (5 minutes)
-        let clock = Clock::get()?;
         require!(
-            clock.unix_timestamp - aggregator.latest_confirmed_round.round_open_timestamp < 300,
-            crate::LotteryError::StalePriceFeed
+            data.len() >= 8 && data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR,
+            RaffleError::InvalidFeedAccount
         );
-        
-        Ok(aggregator.latest_confirmed_round.result.to_f64())
-    }
-}
 
-pub mod pyth {
-    use super::*;
-    
-    pub fn get_price_from_feed(price_feed: &AccountInfo) -> Result<f64> {
-        // Pyth integration would go here
-        // For now, returning placeholder
-        Ok(50000.0) // $50K placeholder for BTC
+        let mut reader = &data[8..];
+        PriceUpdateV2::deserialize(&mut reader).map_err(|_| RaffleError::InvalidFeedAccount.into())
     }
-}
 
-// Price calculation functions
-pub fn calculate_ticket_price_in_lamports(
-    btc_price_feed: &AccountInfo,
-    sol_price_feed: &AccountInfo,
-) -> Result<u64> {
-    let btc_price_usd = switchboard::get_price_from_feed(btc_price_feed)?;
-    let sol_price_usd = switchboard::get_price_from_feed(sol_price_feed)?;
-    
-    // TICKET_PRICE_BTC_SATOSHIS = 5000 (0.00005 BTC)
-    let ticket_value_usd = (crate::TICKET_PRICE_BTC_SATOSHIS as f64 * btc_price_usd) / (10_f64.powi(crate::BTC_DECIMALS as i32));
-    let lamports = (ticket_value_usd / sol_price_usd) * (10_f64.powi(9)); // SOL has 9 decimals
-    
-    Ok(lamports as u64)
-}
+    /// Returns the price, its confidence interval and the slot the update was posted at.
+    pub fn get_price(price_feed: &AccountInfo, feed: &PriceFeed, label: &str) -> Result<(Decimal, Decimal, u64)> {
+        let clock = Clock::get()?;
+        msg!("Parsing {} Pyth price update at {}...", label, clock.unix_timestamp);
+
+        let update = load_price_update(price_feed)?;
+        let message = update.price_message;
 
-pub fn calculate_ticket_price_in_tokens(
-    btc_price_feed: &AccountInfo,
-    token_price_feed: &AccountInfo,
-    token_decimals: u8,
-) -> Result<u64> {
-    let btc_price_usd = switchboard::get_price_from_feed(btc_price_feed)?;
-    let token_price_usd = switchboard::get_price_from_feed(token_price_feed)?;
-    
-    // Calculate ticket value in USD
-    let ticket_value_usd = (crate::TICKET_PRICE_BTC_SATOSHIS as f64 * btc_price_usd) / (10_f64.powi(crate::BTC_DECIMALS as i32));
-    
-    // Convert to token amount
-    let tokens = (ticket_value_usd / token_price_usd) * (10_f64.powi(token_decimals as i32));
-    
-    Ok(tokens as u64)
-}
\ No newline at end of file
+        require!(
+            update.verification_level == VerificationLevel::Full,
+            RaffleError::OracleError
+        );
+        let max_age = i64::try_from(feed.max_staleness).map_err(|_| RaffleError::InvalidOracleConfig)?;
+        require!(
+            message.publish_time.saturating_add(max_age) >= clock.unix_timestamp,
+            RaffleError::OracleError
+        );
+        require!(
+            message.price > 0 && message.exponent <= 0,
+            RaffleError::OracleError
+        );
+
+        let scale = message.exponent.unsigned_abs();
+        let price = Decimal::try_from_i128_with_scale(message.price as i128, scale)
+            .map_err(|_| RaffleError::OracleError)?;
+        let confidence = Decimal::try_from_i128_with_scale(message.conf as i128, scale)
+            .map_err(|_| RaffleError::OracleError)?;
+
+        msg!("{} Price (Decimal): {} ± {}", label, price, confidence);
+
+        Ok((price, confidence, update.posted_slot))
+    }
+}
@@ -0,0 +1,221 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use crate::{AssetOracle, RaffleError, RaffleState, Roles, SupportedToken, ROLE_ORACLE_ADMIN};
+
+pub fn add_supported_token(
+    ctx: Context<AddSupportedToken>,
+    oracle: AssetOracle,
+    min_tickets_per_purchase: u32,
+    max_tickets_per_purchase: u32,
+) -> Result<()> {
+    validate_ticket_limits(min_tickets_per_purchase, max_tickets_per_purchase)?;
+    oracle.validate()?;
+
+    let supported_token = &mut ctx.accounts.supported_token;
+    supported_token.mint = ctx.accounts.token_mint.key();
+    supported_token.decimals = ctx.accounts.token_mint.decimals;
+    supported_token.oracle = oracle;
+    supported_token.enabled = true;
+    supported_token.min_tickets_per_purchase = min_tickets_per_purchase;
+    supported_token.max_tickets_per_purchase = max_tickets_per_purchase;
+    supported_token.bump = ctx.bumps.supported_token;
+
+    msg!("✅ Supported token added: {}", supported_token.mint);
+
+    emit!(SupportedTokenAdded {
+        mint: supported_token.mint,
+        decimals: supported_token.decimals,
+        oracle,
+        min_tickets_per_purchase,
+        max_tickets_per_purchase,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn update_supported_token(
+    ctx: Context<UpdateSupportedToken>,
+    oracle: AssetOracle,
+    min_tickets_per_purchase: u32,
+    max_tickets_per_purchase: u32,
+) -> Result<()> {
+    validate_ticket_limits(min_tickets_per_purchase, max_tickets_per_purchase)?;
+    oracle.validate()?;
+
+    let supported_token = &mut ctx.accounts.supported_token;
+    supported_token.oracle = oracle;
+    supported_token.min_tickets_per_purchase = min_tickets_per_purchase;
+    supported_token.max_tickets_per_purchase = max_tickets_per_purchase;
+
+    msg!("✅ Supported token updated: {}", supported_token.mint);
+
+    emit!(SupportedTokenUpdated {
+        mint: supported_token.mint,
+        oracle,
+        enabled: supported_token.enabled,
+        min_tickets_per_purchase,
+        max_tickets_per_purchase,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Disabling only blocks new purchases; rounds that are already open still settle.
+pub fn set_supported_token_enabled(
+    ctx: Context<UpdateSupportedToken>,
+    enabled: bool,
+) -> Result<()> {
+    let supported_token = &mut ctx.accounts.supported_token;
+    supported_token.enabled = enabled;
+
+    msg!("✅ Supported token {} enabled: {}", supported_token.mint, enabled);
+
+    emit!(SupportedTokenUpdated {
+        mint: supported_token.mint,
+        oracle: supported_token.oracle,
+        enabled,
+        min_tickets_per_purchase: supported_token.min_tickets_per_purchase,
+        max_tickets_per_purchase: supported_token.max_tickets_per_purchase,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn remove_supported_token(ctx: Context<RemoveSupportedToken>) -> Result<()> {
+    let mint = ctx.accounts.supported_token.mint;
+
+    msg!("✅ Supported token removed: {}", mint);
+
+    emit!(SupportedTokenRemoved {
+        mint,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+fn validate_ticket_limits(min_tickets_per_purchase: u32, max_tickets_per_purchase: u32) -> Result<()> {
+    require!(
+        min_tickets_per_purchase > 0 && max_tickets_per_purchase >= min_tickets_per_purchase,
+        RaffleError::InvalidTicketLimits
+    );
+    Ok(())
+}
+
+// Account validation structs
+#[derive(Accounts)]
+pub struct AddSupportedToken<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        constraint = raffle_state.has_role(roles.as_deref(), &authority.key(), ROLE_ORACLE_ADMIN) @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    /// Optional: without it only the authority passes the role check
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SupportedToken::INIT_SPACE,
+        seeds = [b"supported_token", token_mint.key().as_ref()],
+        bump
+    )]
+    pub supported_token: Account<'info, SupportedToken>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSupportedToken<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        constraint = raffle_state.has_role(roles.as_deref(), &authority.key(), ROLE_ORACLE_ADMIN) @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    /// Optional: without it only the authority passes the role check
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"supported_token", supported_token.mint.as_ref()],
+        bump = supported_token.bump
+    )]
+    pub supported_token: Account<'info, SupportedToken>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveSupportedToken<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        constraint = raffle_state.has_role(roles.as_deref(), &authority.key(), ROLE_ORACLE_ADMIN) @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    /// Optional: without it only the authority passes the role check
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"supported_token", supported_token.mint.as_ref()],
+        bump = supported_token.bump
+    )]
+    pub supported_token: Account<'info, SupportedToken>,
+}
+
+// Events
+#[event]
+pub struct SupportedTokenAdded {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub oracle: AssetOracle,
+    pub min_tickets_per_purchase: u32,
+    pub max_tickets_per_purchase: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SupportedTokenUpdated {
+    pub mint: Pubkey,
+    pub oracle: AssetOracle,
+    pub enabled: bool,
+    pub min_tickets_per_purchase: u32,
+    pub max_tickets_per_purchase: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SupportedTokenRemoved {
+    pub mint: Pubkey,
+    pub timestamp: i64,
+}
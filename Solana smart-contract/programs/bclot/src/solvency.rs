@@ -0,0 +1,305 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::{BeneficiaryShare, RaffleError, RaffleState, TokenRaffle};
+
+pub fn get_solvency(ctx: Context<GetSolvency>) -> Result<SolvencyView> {
+    sol_vault_solvency(&ctx.accounts.sol_raffle, &ctx.accounts.vault, &Rent::get()?)
+}
+
+pub fn get_solvency_spl(ctx: Context<GetSolvencySpl>) -> Result<SolvencyView> {
+    Ok(solvency(
+        ctx.accounts.token_vault.amount,
+        0,
+        ctx.accounts.token_raffle.outstanding_obligations,
+    ))
+}
+
+/// Up to `max_amount` of the surplus lamports, such as stray deposits, is credited to
+/// the current beneficiaries, so it is withdrawn like commission.
+/// A deficit is only reported.
+pub fn reconcile(ctx: Context<Reconcile>, max_amount: u64) -> Result<()> {
+    let solvency = sol_vault_solvency(&ctx.accounts.sol_raffle, &ctx.accounts.vault, &Rent::get()?)?;
+
+    sweep_surplus(
+        &mut ctx.accounts.sol_raffle,
+        &ctx.accounts.raffle_state.beneficiaries,
+        &solvency,
+        max_amount,
+        Clock::get()?.unix_timestamp,
+    )
+}
+
+/// Same as `reconcile`, for the token vault of an SPL raffle.
+pub fn reconcile_spl(ctx: Context<ReconcileSpl>, max_amount: u64) -> Result<()> {
+    let solvency = solvency(
+        ctx.accounts.token_vault.amount,
+        0,
+        ctx.accounts.token_raffle.outstanding_obligations,
+    );
+
+    sweep_surplus(
+        &mut ctx.accounts.token_raffle,
+        &ctx.accounts.raffle_state.beneficiaries,
+        &solvency,
+        max_amount,
+        Clock::get()?.unix_timestamp,
+    )
+}
+
+fn sweep_surplus(
+    token_raffle: &mut TokenRaffle,
+    beneficiaries: &[BeneficiaryShare],
+    solvency: &SolvencyView,
+    max_amount: u64,
+    timestamp: i64,
+) -> Result<()> {
+    let surplus_swept = solvency.surplus.min(max_amount);
+
+    if surplus_swept > 0 {
+        token_raffle.credit_commission(beneficiaries, surplus_swept)?;
+        token_raffle.add_obligations(surplus_swept)?;
+
+        emit!(SurplusSwept {
+            token: token_raffle.token_mint,
+            amount: surplus_swept,
+            timestamp,
+        });
+    }
+
+    msg!(
+        "Vault reconciled: balance={} obligations={} surplus={} swept={} deficit={}",
+        solvency.vault_balance,
+        solvency.obligations,
+        solvency.surplus,
+        surplus_swept,
+        solvency.deficit
+    );
+
+    emit!(VaultReconciled {
+        token: token_raffle.token_mint,
+        vault_balance: solvency.vault_balance,
+        obligations: solvency.obligations,
+        surplus: solvency.surplus,
+        surplus_swept,
+        deficit: solvency.deficit,
+        accrued_commission: token_raffle.accrued_commission,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+fn sol_vault_solvency(sol_raffle: &TokenRaffle, vault: &AccountInfo, rent: &Rent) -> Result<SolvencyView> {
+    // The vault has to stay rent exempt, so that part never counts as surplus
+    Ok(solvency(
+        vault.lamports(),
+        rent.minimum_balance(vault.data_len()),
+        sol_raffle.outstanding_obligations,
+    ))
+}
+
+fn solvency(vault_balance: u64, rent_exempt_minimum: u64, obligations: u64) -> SolvencyView {
+    let available = vault_balance.saturating_sub(rent_exempt_minimum);
+
+    SolvencyView {
+        vault_balance,
+        rent_exempt_minimum,
+        obligations,
+        surplus: available.saturating_sub(obligations),
+        deficit: obligations.saturating_sub(available),
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SolvencyView {
+    pub vault_balance: u64, // lamports in sol_vault, or tokens in the token vault
+    pub rent_exempt_minimum: u64, // 0 for token vaults
+    pub obligations: u64, // unclaimed prizes and accrued commission
+    pub surplus: u64,
+    pub deficit: u64,
+}
+
+// Account validation structs
+#[derive(Accounts)]
+pub struct GetSolvency<'info> {
+    #[account(
+        seeds = [b"sol_raffle"],
+        bump = sol_raffle.bump
+    )]
+    pub sol_raffle: Account<'info, TokenRaffle>,
+
+    #[account(
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    /// CHECK: vault for storing SOL
+    pub vault: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetSolvencySpl<'info> {
+    #[account(
+        seeds = [b"token_raffle", token_mint.key().as_ref()],
+        bump = token_raffle.bump,
+        has_one = token_mint @ RaffleError::InvalidTokenMint
+    )]
+    pub token_raffle: Account<'info, TokenRaffle>,
+
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"token_vault", token_mint.key().as_ref()],
+        bump
+    )]
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct Reconcile<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        has_one = authority @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_raffle"],
+        bump = sol_raffle.bump
+    )]
+    pub sol_raffle: Account<'info, TokenRaffle>,
+
+    #[account(
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    /// CHECK: vault for storing SOL
+    pub vault: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReconcileSpl<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        has_one = authority @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        mut,
+        seeds = [b"token_raffle", token_mint.key().as_ref()],
+        bump = token_raffle.bump,
+        has_one = token_mint @ RaffleError::InvalidTokenMint
+    )]
+    pub token_raffle: Account<'info, TokenRaffle>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"token_vault", token_mint.key().as_ref()],
+        bump,
+        token::token_program = token_program
+    )]
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub authority: Signer<'info>,
+}
+
+// Events
+#[event]
+pub struct VaultReconciled {
+    pub token: Pubkey,
+    pub vault_balance: u64,
+    pub obligations: u64,
+    pub surplus: u64,
+    pub surplus_swept: u64, // part of the surplus credited to the beneficiaries
+    pub deficit: u64,
+    pub accrued_commission: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SurplusSwept {
+    pub token: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solvency_reports_surplus_and_deficit() {
+        let balanced = solvency(1_000, 100, 900);
+        assert_eq!((balanced.surplus, balanced.deficit), (0, 0));
+
+        let surplus = solvency(1_500, 100, 900);
+        assert_eq!((surplus.surplus, surplus.deficit), (500, 0));
+
+        let deficit = solvency(800, 100, 900);
+        assert_eq!((deficit.surplus, deficit.deficit), (0, 200));
+
+        // Below the rent-exempt minimum nothing is available
+        let empty = solvency(50, 100, 0);
+        assert_eq!((empty.surplus, empty.deficit), (0, 0));
+    }
+
+    #[test]
+    fn sol_vault_keeps_its_rent_exempt_minimum() {
+        let rent = Rent::default();
+        let rent_exempt_minimum = rent.minimum_balance(0);
+
+        let mut sol_raffle = crate::tests::token_raffle(crate::PriceGuard::DEFAULT);
+        sol_raffle.outstanding_obligations = 1_000_000;
+
+        let key = Pubkey::new_unique();
+        let owner = crate::id();
+        let mut lamports = rent_exempt_minimum + 1_250_000;
+        let mut data = [0u8; 0];
+        let vault = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+
+        let view = sol_vault_solvency(&sol_raffle, &vault, &rent).unwrap();
+        assert_eq!(view.vault_balance, rent_exempt_minimum + 1_250_000);
+        assert_eq!(view.rent_exempt_minimum, rent_exempt_minimum);
+        assert_eq!(view.obligations, 1_000_000);
+        assert_eq!((view.surplus, view.deficit), (250_000, 0));
+    }
+
+    #[test]
+    fn sweep_is_capped_and_credited_to_beneficiaries() {
+        let mut token_raffle = crate::tests::token_raffle(crate::PriceGuard::DEFAULT);
+        token_raffle.outstanding_obligations = 1_000;
+        let beneficiaries = [BeneficiaryShare {
+            recipient: Pubkey::new_unique(),
+            share_bps: 10_000,
+        }];
+
+        sweep_surplus(&mut token_raffle, &beneficiaries, &solvency(1_500, 0, 1_000), 300, 0).unwrap();
+        assert_eq!(token_raffle.accrued_commission, 300);
+        assert_eq!(token_raffle.commission_balances[0].amount, 300);
+        assert_eq!(token_raffle.outstanding_obligations, 1_300);
+
+        // The remaining surplus is below the cap
+        sweep_surplus(&mut token_raffle, &beneficiaries, &solvency(1_500, 0, 1_300), 300, 0).unwrap();
+        assert_eq!(token_raffle.accrued_commission, 500);
+        assert_eq!(token_raffle.outstanding_obligations, 1_500);
+    }
+
+    #[test]
+    fn sweep_leaves_deficits_untouched() {
+        let mut token_raffle = crate::tests::token_raffle(crate::PriceGuard::DEFAULT);
+        token_raffle.outstanding_obligations = 1_000;
+
+        sweep_surplus(&mut token_raffle, &[], &solvency(800, 0, 1_000), u64::MAX, 0).unwrap();
+        assert_eq!(token_raffle.accrued_commission, 0);
+        assert_eq!(token_raffle.outstanding_obligations, 1_000);
+    }
+}
@@ -0,0 +1,800 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use crate::cancellation::PurchaseRefunded;
+use crate::multisig::{require_executable, Multisig, MultisigAction, Proposal, ProposalExecuted};
+use crate::{
+    apply_price_guard, calculate_ticket_price_for_token, create_round,
+    initialize_round_tickets_purchase, load_purchasable_round, record_purchase, save_round,
+    CommissionWithdrawn, NewRoundAccounts, PriceGuard,
+    PrizeClaimed, RaffleError, RaffleState, Round, RoundStatus, RoundTickets, RoundTicketsPurchase,
+    SupportedToken, TicketDenomination, TicketPurchased, TokenRaffle, PAUSE_CLAIMS, PAUSE_PURCHASES,
+    PAUSE_ROUND_CREATION, PAUSE_WITHDRAWALS,
+};
+
+pub fn initialize_token_raffle(ctx: Context<InitializeTokenRaffle>) -> Result<()> {
+    let token_raffle = &mut ctx.accounts.token_raffle;
+
+    token_raffle.authority = ctx.accounts.authority.key();
+    token_raffle.token_mint = ctx.accounts.token_mint.key();
+    token_raffle.total_rounds = 0;
+    token_raffle.ticket_denomination = TicketDenomination::DEFAULT;
+    token_raffle.price_guard = PriceGuard::DEFAULT;
+    token_raffle.last_ticket_price = None;
+    token_raffle.last_ticket_price_at = 0;
+    token_raffle.circuit_breaker_tripped = false;
+    token_raffle.accrued_commission = 0;
+    token_raffle.commission_balances = Vec::new();
+    token_raffle.unassigned_commission = 0;
+    token_raffle.outstanding_obligations = 0;
+    token_raffle.bump = ctx.bumps.token_raffle;
+
+    msg!("TokenRaffle initialized successfully");
+    msg!("Token Raffle PDA: {}", token_raffle.key());
+    msg!("Token mint: {}", token_raffle.token_mint);
+    msg!("Token vault: {}", ctx.accounts.token_vault.key());
+
+    Ok(())
+}
+
+pub fn buy_tickets_spl(
+    ctx: Context<BuyTicketsSpl>,
+    round_id: u32,
+    purchase_index: u32,
+    count: u32,
+    max_cost: u64,
+) -> Result<()> {
+    msg!("Tickets count: {}", count);
+    require!(count > 0, RaffleError::InvalidTicketCount);
+
+    let supported_token = &ctx.accounts.supported_token;
+    require!(
+        count >= supported_token.min_tickets_per_purchase
+            && count <= supported_token.max_tickets_per_purchase,
+        RaffleError::InvalidTicketCount
+    );
+
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let round_info = ctx.accounts.round.to_account_info();
+    let round_tickets_info = ctx.accounts.round_tickets.to_account_info();
+
+    ctx.accounts.raffle_state.require_not_paused(PAUSE_PURCHASES)?;
+    require!(
+        !ctx.accounts.token_raffle.circuit_breaker_tripped,
+        RaffleError::CircuitBreakerTripped
+    );
+
+    let round_exists = round_info.owner == ctx.program_id && round_info.data_len() > 0;
+    let mut new_round_price = None;
+
+    if !round_exists {
+        msg!("🆕 Round {} doesn't exist, creating...", round_id);
+        ctx.accounts.raffle_state.require_not_paused(PAUSE_ROUND_CREATION)?;
+
+        // The price is guarded before the round is created, so a tripped
+        // breaker never leaves an empty round behind in the draw queue
+        let price = calculate_ticket_price_for_token(
+            &ctx.accounts.raffle_state,
+            &ctx.accounts.token_raffle.ticket_denomination,
+            &ctx.accounts.btc_price_feed,
+            &ctx.accounts.btc_secondary_price_feed,
+            supported_token,
+            &ctx.accounts.token_price_feed,
+            &ctx.accounts.token_secondary_price_feed,
+        )?
+        .amount;
+        if !apply_price_guard(&mut ctx.accounts.token_raffle, price, ctx.accounts.player.key(), round_id, count, current_time)? {
+            return ctx.accounts.round_tickets_purchase.close(ctx.accounts.player.to_account_info());
+        }
+        new_round_price = Some(price);
+
+        let locked_ticket_price = if ctx.accounts.raffle_state.price_lock_enabled {
+            msg!("🔒 Ticket price locked for round {}: {} base units", round_id, price);
+            Some(price)
+        } else {
+            None
+        };
+
+        let rent_vault_info = ctx.accounts.rent_vault.to_account_info();
+        let system_program_info = ctx.accounts.system_program.to_account_info();
+
+        create_round(
+            ctx.program_id,
+            &mut ctx.accounts.token_raffle,
+            &ctx.accounts.raffle_state,
+            NewRoundAccounts {
+                round: &round_info,
+                round_tickets: &round_tickets_info,
+                rent_vault: &rent_vault_info,
+                system_program: &system_program_info,
+                round_bump: ctx.bumps.round,
+                round_tickets_bump: ctx.bumps.round_tickets,
+                rent_vault_bump: ctx.bumps.rent_vault,
+            },
+            round_id,
+            current_time,
+            locked_ticket_price,
+        )?;
+    }
+
+    let mut round = load_purchasable_round(
+        &ctx.accounts.token_raffle,
+        &round_info,
+        &round_tickets_info,
+        round_id,
+        current_time,
+    )?;
+
+    let ticket_price = match (round.locked_ticket_price, new_round_price) {
+        (Some(locked_price), _) => {
+            msg!("Using locked ticket price: {} base units", locked_price);
+            locked_price
+        }
+        // Guarded above, when this purchase created the round
+        (None, Some(price)) => price,
+        (None, None) => {
+            let price = calculate_ticket_price_for_token(
+                &ctx.accounts.raffle_state,
+                &round.ticket_denomination,
+                &ctx.accounts.btc_price_feed,
+                &ctx.accounts.btc_secondary_price_feed,
+                &ctx.accounts.supported_token,
+                &ctx.accounts.token_price_feed,
+                &ctx.accounts.token_secondary_price_feed,
+            )?
+            .amount;
+            if !apply_price_guard(&mut ctx.accounts.token_raffle, price, ctx.accounts.player.key(), round_id, count, current_time)? {
+                return ctx.accounts.round_tickets_purchase.close(ctx.accounts.player.to_account_info());
+            }
+            price
+        }
+    };
+    require!(ticket_price > 0, RaffleError::InvalidTicketPrice);
+
+    let token_raffle = &mut ctx.accounts.token_raffle;
+    let round_tickets_purchase = &mut ctx.accounts.round_tickets_purchase;
+
+    initialize_round_tickets_purchase(
+        round_tickets_purchase,
+        round_info.key(),
+        round.purchases_count,
+        ctx.accounts.player.key(),
+        count,
+        ctx.bumps.round_tickets_purchase,
+    )?;
+
+    let cost = ticket_price
+        .checked_mul(count as u64)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    require!(cost <= max_cost, RaffleError::InsufficientSlippage);
+
+    require!(
+        ctx.accounts.player_token_account.amount >= cost,
+        RaffleError::InsufficientFunds
+    );
+
+    let vault_balance_before = ctx.accounts.token_vault.amount;
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.player_token_account.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.token_vault.to_account_info(),
+                authority: ctx.accounts.player.to_account_info(),
+            },
+        ),
+        cost,
+        ctx.accounts.token_mint.decimals,
+    )?;
+
+    // Transfer-fee mints withhold part of the transfer, so the round is
+    // credited with what the vault actually received.
+    ctx.accounts.token_vault.reload()?;
+    let received = ctx
+        .accounts
+        .token_vault
+        .amount
+        .checked_sub(vault_balance_before)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    require!(received > 0, RaffleError::InvalidAmount);
+    msg!("Vault received {} of {} tokens sent", received, cost);
+
+    let (prize_amount, commission_amount) = record_purchase(
+        token_raffle.token_mint,
+        &mut round,
+        round_tickets_purchase,
+        &round_tickets_info,
+        received,
+        current_time,
+    )?;
+    round_tickets_purchase.paid_amount = received;
+    token_raffle.add_obligations(received)?;
+
+    save_round(&round_info, &round)?;
+
+    emit!(TicketPurchased {
+        token: token_raffle.token_mint,
+        round_id: round.round_id,
+        buyer: ctx.accounts.player.key(),
+        count,
+        total_amount: received,
+        prize_amount,
+        commission_amount,
+        denomination: round.ticket_denomination,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+pub fn claim_prize_spl(ctx: Context<ClaimPrizeSpl>, round_id: u32) -> Result<()> {
+    ctx.accounts.raffle_state.require_not_paused(PAUSE_CLAIMS)?;
+
+    let round = &mut ctx.accounts.round;
+
+    require!(
+        round.status == RoundStatus::Completed,
+        RaffleError::RoundNotCompleted
+    );
+    require!(
+        round.winner_address == Some(ctx.accounts.winner.key()),
+        RaffleError::NotTheWinner
+    );
+    require!(!round.prize_claimed, RaffleError::PrizeAlreadyClaimed);
+
+    // Commission was accrued to the treasury ledger when the round completed
+    let prize_amount = round.prize_amount;
+    msg!("prize_amount: {}", prize_amount);
+
+    require!(
+        ctx.accounts.token_vault.amount >= prize_amount,
+        RaffleError::InsufficientVaultBalance
+    );
+
+    let winner_received = pay_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_vault,
+        &ctx.accounts.token_mint,
+        &ctx.accounts.vault_authority,
+        ctx.bumps.vault_authority,
+        &mut ctx.accounts.winner_token_account,
+        prize_amount,
+    )?;
+
+    msg!("✓ Transferred {} tokens to winner ({} received)", prize_amount, winner_received);
+    ctx.accounts.token_raffle.settle_obligations(prize_amount)?;
+
+    let round = &mut ctx.accounts.round;
+    round.prize_claimed = true;
+    emit!(PrizeClaimed {
+        round_id: round.round_id,
+        winner: ctx.accounts.winner.key(),
+    });
+
+    Ok(())
+}
+
+/// Refunds what the vault received for the purchase; a transfer-fee mint
+/// withholds its fee again on the way back.
+pub fn refund_purchase_spl(
+    ctx: Context<RefundPurchaseSpl>,
+    round_id: u32,
+    purchase_index: u32,
+) -> Result<()> {
+    ctx.accounts.raffle_state.require_not_paused(PAUSE_CLAIMS)?;
+
+    require!(
+        ctx.accounts.round.status == RoundStatus::Cancelled,
+        RaffleError::RoundNotCancelled
+    );
+
+    let amount = ctx.accounts.round_tickets_purchase.paid_amount;
+    require!(
+        ctx.accounts.token_vault.amount >= amount,
+        RaffleError::InsufficientVaultBalance
+    );
+
+    let player_received = pay_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_vault,
+        &ctx.accounts.token_mint,
+        &ctx.accounts.vault_authority,
+        ctx.bumps.vault_authority,
+        &mut ctx.accounts.player_token_account,
+        amount,
+    )?;
+    ctx.accounts.token_raffle.settle_obligations(amount)?;
+
+    msg!("✓ Refunded {} tokens to {} ({} received)", amount, ctx.accounts.player.key(), player_received);
+
+    emit!(PurchaseRefunded {
+        token: ctx.accounts.token_mint.key(),
+        round_id,
+        purchase_index,
+        player: ctx.accounts.player.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn withdraw_commission_spl(ctx: Context<WithdrawCommissionSpl>, proposal_id: u64) -> Result<()> {
+    ctx.accounts.raffle_state.require_not_paused(PAUSE_WITHDRAWALS)?;
+
+    let proposal = &ctx.accounts.proposal;
+    require_executable(&ctx.accounts.multisig, proposal, Clock::get()?.unix_timestamp)?;
+    let MultisigAction::WithdrawCommissionSpl { token_mint, amount, recipient } = proposal.action else {
+        return err!(RaffleError::ProposalMismatch);
+    };
+    require_keys_eq!(token_mint, ctx.accounts.token_mint.key(), RaffleError::ProposalMismatch);
+    require_keys_eq!(
+        ctx.accounts.recipient_token_account.owner,
+        recipient,
+        RaffleError::InvalidRecipient
+    );
+    require!(
+        ctx.accounts.token_vault.amount >= amount,
+        RaffleError::InsufficientVaultBalance
+    );
+
+    let token_raffle = &mut ctx.accounts.token_raffle;
+    let recipient_balance = token_raffle.debit_commission(&recipient, amount)?;
+    token_raffle.settle_obligations(amount)?;
+
+    let recipient_received = pay_from_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_vault,
+        &ctx.accounts.token_mint,
+        &ctx.accounts.vault_authority,
+        ctx.bumps.vault_authority,
+        &mut ctx.accounts.recipient_token_account,
+        amount,
+    )?;
+
+    msg!(
+        "✓ Transferred {} tokens of commission to {} ({} received)",
+        amount,
+        recipient,
+        recipient_received
+    );
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    let token_raffle = &ctx.accounts.token_raffle;
+
+    emit!(CommissionWithdrawn {
+        token: token_raffle.token_mint,
+        proposal_id,
+        recipient,
+        amount,
+        recipient_balance,
+        accrued_commission: token_raffle.accrued_commission,
+        timestamp,
+    });
+
+    emit!(ProposalExecuted {
+        proposal_id,
+        action: ctx.accounts.proposal.action.clone(),
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Pays `amount` out of the token vault and returns what `to` actually
+/// received after any transfer fee withheld by the mint.
+fn pay_from_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    token_vault: &InterfaceAccount<'info, TokenAccount>,
+    token_mint: &InterfaceAccount<'info, Mint>,
+    vault_authority: &UncheckedAccount<'info>,
+    vault_authority_bump: u8,
+    to: &mut InterfaceAccount<'info, TokenAccount>,
+    amount: u64,
+) -> Result<u64> {
+    let balance_before = to.amount;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: token_vault.to_account_info(),
+                mint: token_mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: vault_authority.to_account_info(),
+            },
+            &[&[b"vault_authority", &[vault_authority_bump]]],
+        ),
+        amount,
+        token_mint.decimals,
+    )?;
+
+    to.reload()?;
+    to.amount
+        .checked_sub(balance_before)
+        .ok_or(ProgramError::ArithmeticOverflow.into())
+}
+
+// Account validation structs
+#[derive(Accounts)]
+pub struct InitializeTokenRaffle<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        has_one = authority @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"supported_token", token_mint.key().as_ref()],
+        bump = supported_token.bump
+    )]
+    pub supported_token: Account<'info, SupportedToken>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + TokenRaffle::INIT_SPACE,
+        seeds = [b"token_raffle", token_mint.key().as_ref()],
+        bump
+    )]
+    pub token_raffle: Account<'info, TokenRaffle>,
+
+    /// CHECK: PDA that owns every token vault, holds no data
+    #[account(
+        seeds = [b"vault_authority"],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    /// Vault holding the purchases of this token raffle
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"token_vault", token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: u32, purchase_index: u32)]
+pub struct BuyTicketsSpl<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        mut,
+        seeds = [b"token_raffle", token_mint.key().as_ref()],
+        bump = token_raffle.bump,
+        has_one = token_mint @ RaffleError::InvalidTokenMint
+    )]
+    pub token_raffle: Account<'info, TokenRaffle>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"supported_token", token_mint.key().as_ref()],
+        bump = supported_token.bump,
+        constraint = supported_token.enabled @ RaffleError::TokenDisabled
+    )]
+    pub supported_token: Account<'info, SupportedToken>,
+
+    /// CHECK: May not exist - will be created via CPI if needed
+    #[account(
+        mut,
+        seeds = [
+            b"round",
+            token_raffle.key().as_ref(),
+            &round_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub round: UncheckedAccount<'info>,
+
+    /// CHECK: May not exist - will be created via CPI if needed
+    #[account(
+        mut,
+        seeds = [
+            b"round_tickets",
+            round.key().as_ref()
+        ],
+        bump
+    )]
+    pub round_tickets: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"rent_vault"],
+        bump
+    )]
+    pub rent_vault: SystemAccount<'info>,
+
+    #[account(
+        init,
+        payer = player,
+        space = 8 + RoundTicketsPurchase::INIT_SPACE,
+        seeds = [
+            b"round_tickets_purchase",
+            round.key().as_ref(),
+            &purchase_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub round_tickets_purchase: Account<'info, RoundTicketsPurchase>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = player,
+        token::token_program = token_program
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", token_mint.key().as_ref()],
+        bump,
+        token::token_program = token_program
+    )]
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: BTC primary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.btc_oracle.primary.address @ RaffleError::InvalidFeedAccount)]
+    pub btc_price_feed: AccountInfo<'info>,
+
+    /// CHECK: BTC secondary price feed account, pinned in RaffleState
+    #[account(address = raffle_state.btc_oracle.secondary.address @ RaffleError::InvalidFeedAccount)]
+    pub btc_secondary_price_feed: AccountInfo<'info>,
+
+    /// CHECK: Token primary price feed account, pinned in the registry
+    #[account(address = supported_token.oracle.primary.address @ RaffleError::InvalidFeedAccount)]
+    pub token_price_feed: AccountInfo<'info>,
+
+    /// CHECK: Token secondary price feed account, pinned in the registry
+    #[account(address = supported_token.oracle.secondary.address @ RaffleError::InvalidFeedAccount)]
+    pub token_secondary_price_feed: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: u32)]
+pub struct ClaimPrizeSpl<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        mut,
+        seeds = [b"token_raffle", token_mint.key().as_ref()],
+        bump = token_raffle.bump,
+        has_one = token_mint @ RaffleError::InvalidTokenMint
+    )]
+    pub token_raffle: Account<'info, TokenRaffle>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"round",
+            token_raffle.key().as_ref(),
+            &round_id.to_le_bytes()
+        ],
+        bump = round.bump
+    )]
+    pub round: Account<'info, Round>,
+
+    #[account(
+        mut,
+        close = rent_vault,
+        seeds = [
+            b"round_tickets",
+            round.key().as_ref()
+        ],
+        bump
+    )]
+    pub round_tickets: AccountLoader<'info, RoundTickets>,
+
+    #[account(mut)]
+    pub winner: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = winner,
+        token::token_program = token_program
+    )]
+    pub winner_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", token_mint.key().as_ref()],
+        bump,
+        token::token_program = token_program
+    )]
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns every token vault, holds no data
+    #[account(
+        seeds = [b"vault_authority"],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"rent_vault"],
+        bump
+    )]
+    pub rent_vault: SystemAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_id: u32, purchase_index: u32)]
+pub struct RefundPurchaseSpl<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        mut,
+        seeds = [b"token_raffle", token_mint.key().as_ref()],
+        bump = token_raffle.bump,
+        has_one = token_mint @ RaffleError::InvalidTokenMint
+    )]
+    pub token_raffle: Account<'info, TokenRaffle>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [
+            b"round",
+            token_raffle.key().as_ref(),
+            &round_id.to_le_bytes()
+        ],
+        bump = round.bump
+    )]
+    pub round: Account<'info, Round>,
+
+    /// Closed on refund, so it can't be refunded twice
+    #[account(
+        mut,
+        close = player,
+        seeds = [
+            b"round_tickets_purchase",
+            round.key().as_ref(),
+            &purchase_index.to_le_bytes()
+        ],
+        bump = round_tickets_purchase.bump,
+        has_one = player @ RaffleError::Unauthorized
+    )]
+    pub round_tickets_purchase: Account<'info, RoundTicketsPurchase>,
+
+    /// CHECK: receives the purchase rent, matched by `has_one`
+    #[account(mut)]
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::authority = player,
+        token::token_program = token_program
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", token_mint.key().as_ref()],
+        bump,
+        token::token_program = token_program
+    )]
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns every token vault, holds no data
+    #[account(
+        seeds = [b"vault_authority"],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct WithdrawCommissionSpl<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        mut,
+        seeds = [b"token_raffle", token_mint.key().as_ref()],
+        bump = token_raffle.bump,
+        has_one = token_mint @ RaffleError::InvalidTokenMint
+    )]
+    pub token_raffle: Account<'info, TokenRaffle>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault", token_mint.key().as_ref()],
+        bump,
+        token::token_program = token_program
+    )]
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token account owned by the proposal's commission recipient
+    #[account(
+        mut,
+        token::mint = token_mint,
+        token::token_program = token_program
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA that owns every token vault, holds no data
+    #[account(
+        seeds = [b"vault_authority"],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"multisig"],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"proposal", &proposal_id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer @ RaffleError::Unauthorized
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: receives the proposal rent, matched by `has_one`
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
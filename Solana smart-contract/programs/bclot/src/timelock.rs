@@ -0,0 +1,358 @@
+use anchor_lang::prelude::*;
+use crate::price_feeds::BPS_DENOMINATOR;
+use crate::{
+    set_beneficiaries, set_entrance_fee, set_price_feeds, validate_beneficiaries,
+    validate_entrance_fee, AssetOracle, BeneficiaryShare, RaffleError, RaffleState, Roles,
+    MAX_BENEFICIARIES, MAX_PENDING_CHANGES, MAX_TIMELOCK_DELAY, ROLE_ORACLE_ADMIN,
+};
+
+pub fn initialize_pending_changes(ctx: Context<InitializePendingChanges>) -> Result<()> {
+    let pending_changes = &mut ctx.accounts.pending_changes;
+    pending_changes.next_change_id = 0;
+    pending_changes.changes = Vec::new();
+    pending_changes.bump = ctx.bumps.pending_changes;
+
+    msg!("Pending changes queue initialized: {}", pending_changes.key());
+
+    Ok(())
+}
+
+pub fn queue_change(ctx: Context<QueueChange>, action: TimelockedAction) -> Result<()> {
+    let raffle_state = &ctx.accounts.raffle_state;
+    let proposer = ctx.accounts.authority.key();
+
+    let authorized = match action.required_role() {
+        Some(role) => raffle_state.has_role(ctx.accounts.roles.as_deref(), &proposer, role),
+        None => proposer == raffle_state.authority,
+    };
+    require!(authorized, RaffleError::Unauthorized);
+    action.validate()?;
+
+    let pending_changes = &mut ctx.accounts.pending_changes;
+    require!(
+        pending_changes.changes.len() < MAX_PENDING_CHANGES,
+        RaffleError::TooManyPendingChanges
+    );
+
+    let queued_at = Clock::get()?.unix_timestamp;
+    let executable_after = queued_at
+        .checked_add(raffle_state.timelock_delay)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let change_id = pending_changes.next_change_id;
+    pending_changes.next_change_id += 1;
+
+    pending_changes.changes.push(PendingChange {
+        change_id,
+        action: action.clone(),
+        proposer,
+        queued_at,
+        executable_after,
+    });
+
+    msg!("⏳ Change {} queued, executable after {}: {:?}", change_id, executable_after, action);
+
+    emit!(ChangeQueued {
+        change_id,
+        action,
+        proposer,
+        executable_after,
+        timestamp: queued_at,
+    });
+
+    Ok(())
+}
+
+pub fn cancel_change(ctx: Context<CancelChange>, change_id: u64) -> Result<()> {
+    let pending_changes = &mut ctx.accounts.pending_changes;
+    let position = pending_changes.position(change_id)?;
+    let change = pending_changes.changes.remove(position);
+
+    msg!("Change {} cancelled", change_id);
+
+    emit!(ChangeCancelled {
+        change_id,
+        action: change.action,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Permissionless once `executable_after` has passed. Values are validated
+/// again, since the state they were checked against may have moved on.
+pub fn execute_change(ctx: Context<ExecuteChange>, change_id: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+
+    let pending_changes = &mut ctx.accounts.pending_changes;
+    let position = pending_changes.position(change_id)?;
+    require!(
+        now >= pending_changes.changes[position].executable_after,
+        RaffleError::TimelockNotElapsed
+    );
+    let change = pending_changes.changes.remove(position);
+
+    let raffle_state = &mut ctx.accounts.raffle_state;
+    match change.action.clone() {
+        TimelockedAction::UpdateEntranceFee { entrance_fee_bps } => {
+            set_entrance_fee(raffle_state, entrance_fee_bps)?;
+        }
+        TimelockedAction::UpdateBeneficiaries { beneficiaries } => {
+            set_beneficiaries(raffle_state, beneficiaries)?;
+        }
+        TimelockedAction::UpdatePriceFeeds { btc_oracle, sol_oracle } => {
+            set_price_feeds(raffle_state, btc_oracle, sol_oracle)?;
+        }
+        TimelockedAction::UpdateTimelockDelay { delay } => {
+            validate_timelock_delay(delay)?;
+            raffle_state.timelock_delay = delay;
+            msg!("Timelock delay updated: {} seconds", delay);
+        }
+        TimelockedAction::UpdateReserveFloors { rent_reserve_rounds, vrf_reserve_requests } => {
+            raffle_state.rent_reserve_rounds = rent_reserve_rounds;
+            raffle_state.vrf_reserve_requests = vrf_reserve_requests;
+            msg!(
+                "Reserve floors updated: {} rounds of rent, {} VRF requests",
+                rent_reserve_rounds,
+                vrf_reserve_requests
+            );
+        }
+        TimelockedAction::UpdateVaultTopUp { top_up_bps, rent_vault_target, vrf_vault_target } => {
+            raffle_state.vault_top_up_bps = top_up_bps;
+            raffle_state.rent_vault_target = rent_vault_target;
+            raffle_state.vrf_vault_target = vrf_vault_target;
+            msg!(
+                "Vault top-up updated: {} bps, targets rent={} vrf={}",
+                top_up_bps,
+                rent_vault_target,
+                vrf_vault_target
+            );
+        }
+    }
+
+    msg!("✅ Change {} executed", change_id);
+
+    emit!(ChangeExecuted {
+        change_id,
+        action: change.action,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+pub fn get_pending_changes(ctx: Context<GetPendingChanges>) -> Result<Vec<PendingChange>> {
+    Ok(ctx.accounts.pending_changes.changes.clone())
+}
+
+fn validate_timelock_delay(delay: i64) -> Result<()> {
+    require!(
+        (0..=MAX_TIMELOCK_DELAY).contains(&delay),
+        RaffleError::InvalidTimelockDelay
+    );
+    Ok(())
+}
+
+/// Admin action that only takes effect after `RaffleState::timelock_delay`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace, Debug)]
+pub enum TimelockedAction {
+    UpdateEntranceFee {
+        entrance_fee_bps: u16,
+    },
+    UpdateBeneficiaries {
+        #[max_len(MAX_BENEFICIARIES)]
+        beneficiaries: Vec<BeneficiaryShare>,
+    },
+    UpdatePriceFeeds {
+        btc_oracle: AssetOracle,
+        sol_oracle: AssetOracle,
+    },
+    UpdateTimelockDelay {
+        delay: i64, // seconds
+    },
+    UpdateReserveFloors {
+        rent_reserve_rounds: u32,
+        vrf_reserve_requests: u32,
+    },
+    UpdateVaultTopUp {
+        top_up_bps: u16,
+        rent_vault_target: u64, // lamports
+        vrf_vault_target: u64, // lamports
+    },
+}
+
+impl TimelockedAction {
+    /// Role allowed to queue the action besides the authority; `None` means authority only.
+    pub fn required_role(&self) -> Option<u8> {
+        match self {
+            Self::UpdatePriceFeeds { .. } => Some(ROLE_ORACLE_ADMIN),
+            Self::UpdateEntranceFee { .. }
+            | Self::UpdateBeneficiaries { .. }
+            | Self::UpdateTimelockDelay { .. }
+            | Self::UpdateReserveFloors { .. }
+            | Self::UpdateVaultTopUp { .. } => None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::UpdateEntranceFee { entrance_fee_bps } => validate_entrance_fee(*entrance_fee_bps),
+            Self::UpdateBeneficiaries { beneficiaries } => validate_beneficiaries(beneficiaries),
+            Self::UpdatePriceFeeds { btc_oracle, sol_oracle } => {
+                btc_oracle.validate()?;
+                sol_oracle.validate()
+            }
+            Self::UpdateTimelockDelay { delay } => validate_timelock_delay(*delay),
+            Self::UpdateReserveFloors { .. } => Ok(()),
+            Self::UpdateVaultTopUp { top_up_bps, .. } => {
+                require!(*top_up_bps <= BPS_DENOMINATOR, RaffleError::InvalidVaultTopUp);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace, Debug)]
+pub struct PendingChange {
+    pub change_id: u64,
+    pub action: TimelockedAction,
+    pub proposer: Pubkey,
+    pub queued_at: i64,
+    pub executable_after: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PendingChanges {
+    pub next_change_id: u64,
+    #[max_len(MAX_PENDING_CHANGES)]
+    pub changes: Vec<PendingChange>,
+    pub bump: u8,
+}
+
+impl PendingChanges {
+    fn position(&self, change_id: u64) -> Result<usize> {
+        self.changes
+            .iter()
+            .position(|change| change.change_id == change_id)
+            .ok_or(RaffleError::ChangeNotFound.into())
+    }
+}
+
+// Account validation structs
+#[derive(Accounts)]
+pub struct InitializePendingChanges<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        has_one = authority @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PendingChanges::INIT_SPACE,
+        seeds = [b"pending_changes"],
+        bump
+    )]
+    pub pending_changes: Account<'info, PendingChanges>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct QueueChange<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    /// Optional: without it only the authority passes the role check
+    #[account(
+        seeds = [b"roles"],
+        bump = roles.bump
+    )]
+    pub roles: Option<Account<'info, Roles>>,
+
+    #[account(
+        mut,
+        seeds = [b"pending_changes"],
+        bump = pending_changes.bump
+    )]
+    pub pending_changes: Account<'info, PendingChanges>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelChange<'info> {
+    #[account(
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump,
+        has_one = authority @ RaffleError::Unauthorized
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        mut,
+        seeds = [b"pending_changes"],
+        bump = pending_changes.bump
+    )]
+    pub pending_changes: Account<'info, PendingChanges>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"raffle_state"],
+        bump = raffle_state.bump
+    )]
+    pub raffle_state: Account<'info, RaffleState>,
+
+    #[account(
+        mut,
+        seeds = [b"pending_changes"],
+        bump = pending_changes.bump
+    )]
+    pub pending_changes: Account<'info, PendingChanges>,
+}
+
+#[derive(Accounts)]
+pub struct GetPendingChanges<'info> {
+    #[account(
+        seeds = [b"pending_changes"],
+        bump = pending_changes.bump
+    )]
+    pub pending_changes: Account<'info, PendingChanges>,
+}
+
+// Events
+#[event]
+pub struct ChangeQueued {
+    pub change_id: u64,
+    pub action: TimelockedAction,
+    pub proposer: Pubkey,
+    pub executable_after: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChangeCancelled {
+    pub change_id: u64,
+    pub action: TimelockedAction,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChangeExecuted {
+    pub change_id: u64,
+    pub action: TimelockedAction,
+    pub timestamp: i64,
+}
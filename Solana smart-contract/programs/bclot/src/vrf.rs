@@ -10,8 +10,10 @@ use orao_solana_vrf_cb::{
     RequestParams, CB_CLIENT_ACCOUNT_SEED, CB_CONFIG_ACCOUNT_SEED, CB_REQUEST_ACCOUNT_SEED,
 };
 use bytemuck::Pod;
-use crate::{Round, TokenRaffle, RoundStatus, RaffleError};
+use crate::{CommissionAccrued, CommissionShareAccrued, Round, TokenRaffle, RoundStatus, RaffleError, RaffleState};
+use crate::price_feeds::BPS_DENOMINATOR;
 use crate::change_round_status;
+use crate::cancellation::RoundCancelled;
 use crate::CLIENT_STATE_SEED;
 use crate::ClientState;
 use crate::RoundTickets;
@@ -66,7 +68,13 @@ pub fn consume_randomness(
         .find(|acc| acc.key == &round_tickets_pubkey)
         .ok_or(RaffleError::NotEnoughRemainingAccounts)?;
 
-    pick_winner(sol_raffle, round, round_tickets_info, random_number)?;
+    let top_up = if sol_raffle.is_sol() {
+        VaultTopUp::from_remaining_accounts(ctx.remaining_accounts)?
+    } else {
+        None
+    };
+
+    pick_winner(sol_raffle, round, round_tickets_info, random_number, top_up)?;
 
     sol_raffle.pending_rounds.retain(|&id| id != round_id);
     
@@ -78,6 +86,7 @@ fn pick_winner(
     round: &mut Account<Round>,
     round_tickets_info: &AccountInfo,
     random_number: u64,
+    top_up: Option<VaultTopUp>,
 ) -> Result<()> {
     msg!("TEST pick_winner");
 
@@ -86,6 +95,29 @@ fn pick_winner(
         return Ok(());
     }
 
+    // Randomness requested before the round was cancelled; its purchases are refunded instead
+    if round.status == RoundStatus::Cancelled {
+        msg!("⚠️ Round {} was cancelled, skipping pick_winner", round.round_id);
+        return Ok(());
+    }
+
+    let Some(winner_ticket_index) = draw_ticket_index(random_number, round.total_tickets) else {
+        // Nobody bought a ticket, e.g. a round opened by `initialize_round`: there is no
+        // winner and nothing to refund, so the round is cancelled and leaves the draw queue
+        msg!("⚠️ Round {} has no tickets, cancelling it", round.round_id);
+        change_round_status(sol_raffle, round, RoundStatus::Cancelled)?;
+
+        emit!(RoundCancelled {
+            token: sol_raffle.token_mint,
+            round_id: round.round_id,
+            cancelled_by: crate::id(),
+            refundable_amount: 0,
+            vrf_pending: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        return Ok(());
+    };
+
     // Ensure the account has enough allocated space to safely read or modify its content
     let data = round_tickets_info.try_borrow_data()?;
     require!(
@@ -98,7 +130,6 @@ fn pick_winner(
     let tickets = round_tickets.get_tickets();
     msg!("tickets_length: {}", tickets.len());
     
-    let winner_ticket_index = (random_number % round.total_tickets as u64) as u32;
     msg!("Winner ticket index: {}", winner_ticket_index);
 
     let purchase_index = tickets.partition_point(|&c| c <= winner_ticket_index);
@@ -116,6 +147,45 @@ fn pick_winner(
 
     let clock = Clock::get()?;
 
+    let topped_up = match top_up {
+        Some(top_up) => top_up.apply(round, clock.unix_timestamp)?,
+        None if sol_raffle.is_sol() => {
+            msg!("⚠️ Vault top-up accounts missing, round {} settles without a top-up", round.round_id);
+            emit!(VaultTopUpSkipped {
+                round_id: round.round_id,
+                timestamp: clock.unix_timestamp,
+            });
+            0
+        }
+        None => 0,
+    };
+    sol_raffle.settle_obligations(topped_up)?;
+    let commission = round.commission_balance - topped_up;
+
+    // Move the rest of the round's commission to the treasury ledger; it no longer waits for the prize claim.
+    // It is credited to the beneficiaries snapshotted when the round was created.
+    let shares = sol_raffle.credit_commission(&round.beneficiaries, commission)?;
+
+    for (share, &(amount, assigned)) in round.beneficiaries.iter().zip(shares.iter()) {
+        emit!(CommissionShareAccrued {
+            token: sol_raffle.token_mint,
+            round_id: round.round_id,
+            recipient: share.recipient,
+            share_bps: share.share_bps,
+            amount,
+            assigned,
+            timestamp: clock.unix_timestamp,
+        });
+    }
+
+    emit!(CommissionAccrued {
+        token: sol_raffle.token_mint,
+        round_id: round.round_id,
+        amount: commission,
+        accrued_commission: sol_raffle.accrued_commission,
+        timestamp: clock.unix_timestamp,
+    });
+
     emit!(WinnerPicked {
         token: sol_raffle.token_mint,
         round: round.key(),
@@ -130,6 +200,94 @@ fn pick_winner(
     Ok(())
 }
 
+/// Index of the winning ticket among `total_tickets`, `None` for a round without tickets.
+fn draw_ticket_index(random_number: u64, total_tickets: u32) -> Option<u32> {
+    random_number
+        .checked_rem(total_tickets as u64)
+        .map(|index| index as u32)
+}
+
+/// Accounts attached to SOL raffle callbacks for topping up the vaults from commission.
+/// Callbacks requested without them settle without a top-up and emit `VaultTopUpSkipped`.
+struct VaultTopUp<'a, 'info> {
+    top_up_bps: u16,
+    rent_vault_target: u64,
+    vrf_vault_target: u64,
+    sol_vault: &'a AccountInfo<'info>,
+    rent_vault: &'a AccountInfo<'info>,
+    vrf_fee_vault: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> VaultTopUp<'a, 'info> {
+    fn from_remaining_accounts(accounts: &'a [AccountInfo<'info>]) -> Result<Option<Self>> {
+        let find = |seed: &[u8]| {
+            let (pubkey, _bump) = Pubkey::find_program_address(&[seed], &crate::id());
+            accounts.iter().find(|acc| acc.key == &pubkey)
+        };
+
+        let (Some(raffle_state), Some(sol_vault), Some(rent_vault), Some(vrf_fee_vault)) = (
+            find(b"raffle_state"),
+            find(b"sol_vault"),
+            find(b"rent_vault"),
+            find(b"vrf_fee_vault"),
+        ) else {
+            return Ok(None);
+        };
+
+        require_keys_eq!(*raffle_state.owner, crate::id(), ErrorCode::AccountOwnedByWrongProgram);
+        let raffle_state = RaffleState::try_deserialize(&mut &raffle_state.try_borrow_data()?[..])?;
+
+        Ok(Some(Self {
+            top_up_bps: raffle_state.vault_top_up_bps,
+            rent_vault_target: raffle_state.rent_vault_target,
+            vrf_vault_target: raffle_state.vrf_vault_target,
+            sol_vault,
+            rent_vault,
+            vrf_fee_vault,
+        }))
+    }
+
+    /// Moves up to `vault_top_up_bps` of the round's commission from `sol_vault`,
+    /// filling `rent_vault` first and then `vrf_fee_vault`, never past their targets.
+    /// Returns the lamports moved; they are no longer part of the accrued commission.
+    fn apply(&self, round: &Round, timestamp: i64) -> Result<u64> {
+        let mut budget = (round.commission_balance as u128 * self.top_up_bps as u128
+            / BPS_DENOMINATOR as u128) as u64;
+        let mut topped_up = 0;
+
+        for (vault, target) in [
+            (self.rent_vault, self.rent_vault_target),
+            (self.vrf_fee_vault, self.vrf_vault_target),
+        ] {
+            let amount = target.saturating_sub(vault.lamports()).min(budget);
+            if amount == 0 {
+                continue;
+            }
+
+            **self.sol_vault.try_borrow_mut_lamports()? = self
+                .sol_vault
+                .lamports()
+                .checked_sub(amount)
+                .ok_or(RaffleError::InsufficientVaultBalance)?;
+            **vault.try_borrow_mut_lamports()? += amount;
+            budget -= amount;
+            topped_up += amount;
+
+            msg!("Topped up {} with {} lamports from round {} commission", vault.key, amount, round.round_id);
+
+            emit!(VaultToppedUp {
+                vault: vault.key(),
+                round_id: round.round_id,
+                amount,
+                vault_balance: vault.lamports(),
+                timestamp,
+            });
+        }
+
+        Ok(topped_up)
+    }
+}
+
 // Account validation structs
 #[derive(Accounts)]
 pub struct InitializeClientState<'info> {
@@ -180,10 +338,10 @@ pub struct ConsumeRandomness<'info> {
     )]
     pub request: Account<'info, RequestAccount>,
 
+    /// SOL raffle or any per-mint token raffle
     #[account(
         mut,
-        seeds = [b"sol_raffle"],
-        bump = sol_raffle.bump
+        constraint = sol_raffle.key() == sol_raffle.address()? @ RaffleError::InvalidTokenRaffle
     )]
     pub sol_raffle: Account<'info, TokenRaffle>,
 
@@ -209,4 +367,109 @@ pub struct WinnerPicked {
     pub winner_ticket_index: u32,
     pub prize_amount: u64,
     pub timestamp: i64,
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct VaultToppedUp {
+    pub vault: Pubkey,
+    pub round_id: u32,
+    pub amount: u64,
+    pub vault_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultTopUpSkipped {
+    pub round_id: u32,
+    pub timestamp: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL_VAULT_BALANCE: u64 = 10_000_000;
+
+    fn apply_top_up(
+        top_up_bps: u16,
+        commission: u64,
+        (rent_vault_balance, rent_vault_target): (u64, u64),
+        (vrf_vault_balance, vrf_vault_target): (u64, u64),
+    ) -> Result<(u64, [u64; 3])> {
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut lamports = [SOL_VAULT_BALANCE, rent_vault_balance, vrf_vault_balance];
+        let mut data = [[0u8; 0]; 3];
+        let owners = [crate::id(), Pubkey::default(), Pubkey::default()];
+
+        let [sol_lamports, rent_lamports, vrf_lamports] = &mut lamports;
+        let [sol_data, rent_data, vrf_data] = &mut data;
+        let sol_vault = AccountInfo::new(&keys[0], false, true, sol_lamports, sol_data, &owners[0], false, 0);
+        let rent_vault = AccountInfo::new(&keys[1], false, true, rent_lamports, rent_data, &owners[1], false, 0);
+        let vrf_fee_vault = AccountInfo::new(&keys[2], false, true, vrf_lamports, vrf_data, &owners[2], false, 0);
+
+        let top_up = VaultTopUp {
+            top_up_bps,
+            rent_vault_target,
+            vrf_vault_target,
+            sol_vault: &sol_vault,
+            rent_vault: &rent_vault,
+            vrf_fee_vault: &vrf_fee_vault,
+        };
+        let mut round = crate::tests::round(500);
+        round.commission_balance = commission;
+
+        let topped_up = top_up.apply(&round, 0)?;
+        Ok((topped_up, [sol_vault.lamports(), rent_vault.lamports(), vrf_fee_vault.lamports()]))
+    }
+
+    #[test]
+    fn draw_picks_a_ticket_within_the_round() {
+        assert_eq!(draw_ticket_index(17, 5), Some(2));
+        assert_eq!(draw_ticket_index(u64::MAX, 1), Some(0));
+        assert_eq!(draw_ticket_index(u64::MAX, u32::MAX), Some((u64::MAX % u32::MAX as u64) as u32));
+    }
+
+    #[test]
+    fn draw_without_tickets_has_no_winner() {
+        assert_eq!(draw_ticket_index(0, 0), None);
+        assert_eq!(draw_ticket_index(42, 0), None);
+    }
+
+    #[test]
+    fn top_up_fills_rent_vault_first() {
+        // 20% of 1_000_000 commission: 50_000 reach the rent vault target, the rest goes to the VRF vault
+        let (topped_up, balances) =
+            apply_top_up(2_000, 1_000_000, (50_000, 100_000), (0, 1_000_000)).unwrap();
+        assert_eq!(topped_up, 200_000);
+        assert_eq!(balances, [SOL_VAULT_BALANCE - 200_000, 100_000, 150_000]);
+    }
+
+    #[test]
+    fn top_up_stops_at_targets() {
+        let (topped_up, balances) =
+            apply_top_up(10_000, 1_000_000, (90_000, 100_000), (40_000, 50_000)).unwrap();
+        assert_eq!(topped_up, 20_000);
+        assert_eq!(balances, [SOL_VAULT_BALANCE - 20_000, 100_000, 50_000]);
+    }
+
+    #[test]
+    fn top_up_skips_vaults_above_target() {
+        let (topped_up, balances) =
+            apply_top_up(10_000, 1_000_000, (200_000, 100_000), (60_000, 50_000)).unwrap();
+        assert_eq!(topped_up, 0);
+        assert_eq!(balances, [SOL_VAULT_BALANCE, 200_000, 60_000]);
+    }
+
+    #[test]
+    fn top_up_disabled_moves_nothing() {
+        let (topped_up, balances) = apply_top_up(0, 1_000_000, (0, 100_000), (0, 100_000)).unwrap();
+        assert_eq!(topped_up, 0);
+        assert_eq!(balances, [SOL_VAULT_BALANCE, 0, 0]);
+    }
+
+    #[test]
+    fn top_up_fails_when_sol_vault_is_short() {
+        let result = apply_top_up(10_000, SOL_VAULT_BALANCE + 1, (0, u64::MAX), (0, 0));
+        assert_eq!(result.unwrap_err(), RaffleError::InsufficientVaultBalance.into());
+    }
+}